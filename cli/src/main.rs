@@ -1,23 +1,48 @@
 #![allow(special_module_name)]
+use chrono::format::{Item, StrftimeItems};
+use chrono::TimeDelta;
 use clap::Parser;
+use std::fs::File;
+use std::io::Write;
 use std::num::ParseIntError;
 use std::path::PathBuf;
 
+use fit2srt_core::chapter;
+use fit2srt_core::srt_iter::SrtIter;
+use fit2srt_core::subtitle;
+use fit2srt_core::waypoint;
+use fit2srt_core::Clip;
+use fit2srt_core::DiveSites;
+use fit2srt_core::Event;
+use fit2srt_core::Filter;
+use fit2srt_core::FrameRate;
+use fit2srt_core::Gap;
+use fit2srt_core::Interpolation;
+use fit2srt_core::Lang;
+use fit2srt_core::Locale;
+use fit2srt_core::Metric;
+use fit2srt_core::Overlap;
+use fit2srt_core::Overlay;
+use fit2srt_core::ProfileChart;
+use fit2srt_core::Quirks;
+use fit2srt_core::SafetyStop;
 use fit2srt_core::SrtGenerator;
-use fit2srt_core::Summary;
+use fit2srt_core::SummaryCue;
+use fit2srt_core::SummaryPosition;
+use fit2srt_core::VideoMap;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    /// Generate subtitles after 00:00:00
+    /// Generate subtitles after 00:00:00 (or 00:00:00.000)
     #[arg(short, long)]
     after: Option<String>,
 
-    /// Generate subtitles before 00:00:00
+    /// Generate subtitles before 00:00:00 (or 00:00:00.000)
     #[arg(short, long)]
     before: Option<String>,
 
-    /// Generate subtitles start from 00:00:00
+    /// Generate subtitles start from 00:00:00 (or 00:00:00.000)
     /// This option is used if you recording a video before the dive computer started
     /// If you record after dive computer started, you do not need this.
     #[arg(short, long)]
@@ -27,9 +52,200 @@ struct Cli {
     #[arg(short, long)]
     no_summary: bool,
 
+    /// A template file for the dive summary, `{field}` is replaced by the value of the field,
+    /// a line is skipped if a field has no value, and `[...]` skips only the part.
+    /// Fields: date, time, dive_time, location, coordinates, avg_depth, max_depth, avg_temperature,
+    /// min_temperature, gas, setpoints, min_ppo2, max_ppo2, start_pressure, end_pressure,
+    /// surface_interval, dive_number, dive_of_day, residual_n2, device, serial_number,
+    /// and the developer fields of the session, dev:NAME or dev:INDEX:NAME
+    #[arg(long)]
+    summary_template: Option<PathBuf>,
+
+    /// The seconds from the start of the first cue or the end of the last cue to the summary,
+    /// up to a day
+    #[arg(long, default_value_t = 5.0, value_parser = parse_summary_delay)]
+    summary_delay: f64,
+
+    /// The seconds to show the summary, up to a day
+    #[arg(long, default_value_t = 10.0, value_parser = parse_summary_duration)]
+    summary_duration: f64,
+
+    /// Where to show the summary: start, end or both
+    #[arg(long, default_value = "end")]
+    summary_position: SummaryPosition,
+
+    /// Show the summary of each FIT file, with the dive of the day and the surface interval,
+    /// instead of one summary of all files
+    #[arg(long)]
+    summary_per_file: bool,
+
+    /// Name the location with a dive site database, a CSV of name,latitude,longitude or a GeoJSON,
+    /// or `example` for a few well known sites at approximate positions
+    #[arg(long)]
+    dive_sites: Option<String>,
+
+    /// The distance in metres to match a dive site
+    #[arg(long, default_value_t = 1000.0)]
+    site_tolerance: f64,
+
+    /// Correct the fields of devices with a CSV of manufacturer,product,field,name,scale,offset,units,
+    /// an empty product is for all products of the manufacturer
+    #[arg(long)]
+    quirks: Option<PathBuf>,
+
+    /// The languages of the labels: en, zh or ja, a subtitle is written for each language,
+    /// ex: --lang en,zh,ja writes dive.en.srt, dive.zh.srt and dive.ja.srt for --output dive.srt
+    #[arg(long, value_delimiter = ',', default_value = "en")]
+    lang: Vec<Lang>,
+
+    /// Write the decimals with a comma, ex: 4,2m
+    #[arg(long)]
+    decimal_comma: bool,
+
+    /// The format of the date in the summary, ex: %d/%m/%Y, the one of the language by default
+    #[arg(long, value_parser = parse_date_format)]
+    date_format: Option<String>,
+
+    /// Write the subtitle to the file instead of the standard output,
+    /// the language is added before the extension for more than one language,
+    /// which is next to the first FIT file if it is not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Merge an existing SRT or WebVTT subtitle of the video, ex: the narration
+    #[arg(long)]
+    subtitle: Option<PathBuf>,
+
+    /// Show the cues overlapping the subtitle: stack the lines in one cue,
+    /// or position the dive data at the top of the video
+    #[arg(long, default_value = "stack")]
+    overlap: Overlap,
+
+    /// The values shown in the cues: depth, heart_rate, temperature, heading,
+    /// dive_time (since the descent), elapsed_time (since the start of the file), clock,
+    /// ppo2, setpoint (of a rebreather),
+    /// or a developer field, dev:NAME or dev:INDEX:NAME with the developer data index, ex: the PPO2 cells
+    #[arg(long, value_delimiter = ',', default_value = "depth")]
+    metric: Vec<Metric>,
+
+    /// The format of the clock metric, ex: %H:%M:%S, %I:%M %p
+    #[arg(long, value_parser = parse_clock_format, default_value = "%H:%M")]
+    clock_format: String,
+
+    /// Emit a cue only if the value changes more than the threshold, METRIC=VALUE, ex: temperature=1
+    #[arg(long, value_parser = parse_threshold)]
+    threshold: Vec<(Metric, f64)>,
+
+    /// Filter the samples of a metric before the cues, METRIC=FILTER, applied in the order given,
+    /// average:SECONDS, median:SECONDS or max_rate:PER_SECOND, ex: depth=max_rate:2 depth=median:3
+    #[arg(long, value_parser = parse_filter)]
+    filter: Vec<(Metric, Filter)>,
+
+    /// Show the thermocline where the temperature drops at least the gradient (C per metre)
+    #[arg(long, num_args = 0..=1, default_missing_value = "0.5")]
+    thermocline: Option<f64>,
+
+    /// The seconds to smooth the heading of the compass, up to an hour
+    #[arg(long, default_value_t = 5.0, value_parser = parse_heading_smoothing)]
+    heading_smoothing: f64,
+
+    /// Show a cue when the heading turns at least the degrees in 30 seconds
+    #[arg(long, num_args = 0..=1, default_missing_value = "150")]
+    turnaround: Option<f64>,
+
+    /// Show alarms and events as short cues, all kinds without a list, or some of
+    /// depth_alarm, time_alarm, safety_stop, ascent_rate, gas_switch, bookmark, battery_low,
+    /// ppo2_alarm, setpoint_switch, bailout
+    #[arg(
+        long,
+        value_delimiter = ',',
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "depth_alarm,time_alarm,safety_stop,ascent_rate,gas_switch,bookmark,battery_low,ppo2_alarm,setpoint_switch,bailout"
+    )]
+    events: Vec<Event>,
+
+    /// Count down the safety stop of the seconds, 180 without a value, in the band of 3 to 6 metres
+    /// after the deepest part of the dive, or from the safety stop events of the dive computer
+    #[arg(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "180",
+        value_parser = parse_safety_stop
+    )]
+    safety_stop: Option<f64>,
+
+    /// Snap the cues on the frames of the video, ex: 23.976, 25, 29.97df, 50, 60
+    #[arg(long)]
+    fps: Option<FrameRate>,
+
+    /// Write the times of cues as SMPTE timecode of the frame rate from `--fps`
+    #[arg(long, requires = "fps")]
+    timecode: bool,
+
+    /// The time between FIT files on the video: keep, remove or the seconds of the gap, up to a day
+    #[arg(long, default_value = "keep")]
+    gap: Gap,
+
+    /// Interpolate values between samples, linear or spline
+    #[arg(long)]
+    interpolate: Option<Interpolation>,
+
+    /// The rate (Hz) to emit interpolated values, up to 100 Hz
+    #[arg(long, value_parser = parse_rate, default_value = "4")]
+    rate: f64,
+
+    /// Write a srt for a clip of the video, START+DURATION[=OUTPUT], ex: 10:02:11+00:12:31=GX010001.srt
+    /// The output is clip-N.srt if it is not given
+    #[arg(long, value_parser = parse_clip)]
+    clip: Vec<ClipArg>,
+
+    /// Export the entry and exit positions of the dives, GPX or KML by the extension
+    #[arg(long)]
+    export: Option<PathBuf>,
+
+    /// Write the laps and the bookmarks as chapters by the extension,
+    /// .txt for a YouTube description, .ffmetadata, .vtt or .xml for Matroska
+    #[arg(long)]
+    chapters: Option<PathBuf>,
+
+    /// Render the depth profile as PNG frames into the directory, one frame per frame of `--fps`,
+    /// or every second without it
+    #[arg(long)]
+    profile_png: Option<PathBuf>,
+
+    /// Write the depth profile as ASS vector drawings to overlay on the video
+    #[arg(long)]
+    profile_ass: Option<PathBuf>,
+
+    /// The size of the profile chart, WIDTHxHEIGHT
+    #[arg(long, value_parser = parse_size, default_value = "320x120")]
+    profile_size: (u32, u32),
+
+    /// The background of the profile chart, RRGGBB or RRGGBBAA, transparent by default
+    #[arg(long, value_parser = parse_colour, default_value = "00000000")]
+    profile_background: [u8; 4],
+
+    /// Render transparent PNG frames at the frame rate of `--fps` into the directory,
+    /// with a depth gauge, the time, the temperature and a compass, to burn into the video
+    #[arg(long, requires = "fps")]
+    overlay: Option<PathBuf>,
+
+    /// The size of the video to place the ASS profile and the overlay on, WIDTHxHEIGHT
+    #[arg(long, value_parser = parse_size, default_value = "1920x1080")]
+    video_size: (u32, u32),
+
+    /// The FIT files, which are ordered by their start times
     fit_files: Vec<PathBuf>,
 }
 
+#[derive(Clone)]
+struct ClipArg {
+    clip: Clip,
+    output: Option<PathBuf>,
+}
+
 fn time_to_vec(time_str: &str) -> Result<Vec<u32>, ParseIntError> {
     if time_str.contains(':') {
         let mut err = None;
@@ -48,12 +264,156 @@ fn time_to_vec(time_str: &str) -> Result<Vec<u32>, ParseIntError> {
         } else {
             Ok(time)
         }
+    } else if time_str.len() == 6 {
+        (0..6)
+            .step_by(2)
+            .map(|i| time_str.get(i..i + 2).unwrap_or_default().parse())
+            .collect()
+    } else {
+        // not HHMMSS, rejected as out of range
+        Ok(Vec::new())
+    }
+}
+
+/// Parse `15:01:30`, `150130` with an optional millisecond part, `15:01:30.450`
+/// None if the time is out of range
+fn time_to_delta(time_str: &str) -> Result<Option<TimeDelta>, ParseIntError> {
+    let (hms_str, ms) = if let Some((hms_str, frac_str)) = time_str.split_once(['.', ',']) {
+        if !frac_str.bytes().all(|b| b.is_ascii_digit()) {
+            // the parse error of the fraction, or a sign rejected as out of range
+            return frac_str.parse::<u32>().map(|_| None);
+        }
+        let digits = &frac_str[..frac_str.len().min(3)];
+        (
+            hms_str,
+            digits.parse::<u32>()? * 10u32.pow(3 - digits.len() as u32),
+        )
+    } else {
+        (time_str, 0)
+    };
+    let hms = time_to_vec(hms_str)?;
+    if hms.len() == 3 && hms[0] <= 24 && hms[1] < 60 && hms[2] < 60 {
+        Ok(Some(
+            TimeDelta::hours(hms[0] as i64)
+                + TimeDelta::minutes(hms[1] as i64)
+                + TimeDelta::seconds(hms[2] as i64)
+                + TimeDelta::milliseconds(ms as i64),
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
+fn parse_clip(clip_str: &str) -> Result<ClipArg, String> {
+    let (time_str, output) = if let Some((time_str, output)) = clip_str.split_once('=') {
+        (time_str, Some(PathBuf::from(output)))
+    } else {
+        (clip_str, None)
+    };
+    let Some((start_str, duration_str)) = time_str.split_once('+') else {
+        return Err("clip should be START+DURATION".to_string());
+    };
+    match (time_to_delta(start_str), time_to_delta(duration_str)) {
+        (Ok(Some(start)), Ok(Some(duration))) => Ok(ClipArg {
+            clip: Clip { start, duration },
+            output,
+        }),
+        _ => Err("invalid time of clip".to_string()),
+    }
+}
+
+fn parse_threshold(threshold_str: &str) -> Result<(Metric, f64), String> {
+    let Some((metric_str, value_str)) = threshold_str.split_once('=') else {
+        return Err("threshold should be METRIC=VALUE".to_string());
+    };
+    let metric = metric_str.parse::<Metric>().map_err(|e| e.to_string())?;
+    match value_str.parse::<f64>() {
+        Ok(value) if value >= 0.0 => Ok((metric, value)),
+        _ => Err("invalid threshold".to_string()),
+    }
+}
+
+fn parse_filter(filter_str: &str) -> Result<(Metric, Filter), String> {
+    let Some((metric_str, filter_str)) = filter_str.split_once('=') else {
+        return Err("filter should be METRIC=FILTER".to_string());
+    };
+    let metric = metric_str.parse::<Metric>().map_err(|e| e.to_string())?;
+    let filter = filter_str.parse::<Filter>().map_err(|e| e.to_string())?;
+    Ok((metric, filter))
+}
+
+fn parse_rate(rate_str: &str) -> Result<f64, String> {
+    match rate_str.parse::<f64>() {
+        // at least 10 milliseconds between the interpolated values
+        Ok(rate) if rate > 0.0 && rate <= 100.0 => Ok(rate),
+        _ => Err("rate should be a number of Hz in (0, 100]".to_string()),
+    }
+}
+
+fn parse_safety_stop(seconds_str: &str) -> Result<f64, String> {
+    match seconds_str.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds <= 3600.0 => Ok(seconds),
+        _ => Err("safety stop should be a number of seconds in (0, 3600]".to_string()),
+    }
+}
+
+fn parse_summary_delay(seconds_str: &str) -> Result<f64, String> {
+    match seconds_str.parse::<f64>() {
+        Ok(seconds) if (0.0..=86400.0).contains(&seconds) => Ok(seconds),
+        _ => Err("summary delay should be a number of seconds in [0, 86400]".to_string()),
+    }
+}
+
+fn parse_summary_duration(seconds_str: &str) -> Result<f64, String> {
+    match seconds_str.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds <= 86400.0 => Ok(seconds),
+        _ => Err("summary duration should be a number of seconds in (0, 86400]".to_string()),
+    }
+}
+
+fn parse_heading_smoothing(seconds_str: &str) -> Result<f64, String> {
+    match seconds_str.parse::<f64>() {
+        Ok(seconds) if (0.0..=3600.0).contains(&seconds) => Ok(seconds),
+        _ => Err("heading smoothing should be a number of seconds in [0, 3600]".to_string()),
+    }
+}
+
+fn parse_size(size_str: &str) -> Result<(u32, u32), String> {
+    match size_str
+        .split_once(['x', 'X'])
+        .map(|(w, h)| (w.parse(), h.parse()))
+    {
+        Some((Ok(width), Ok(height))) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err("size should be WIDTHxHEIGHT".to_string()),
+    }
+}
+
+fn parse_colour(colour_str: &str) -> Result<[u8; 4], String> {
+    let hex = colour_str.trim_start_matches('#');
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or_default(), 16);
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Ok(r), Ok(g), Ok(b)) => Ok([r, g, b, 255]),
+        (8, Ok(r), Ok(g), Ok(b)) => match channel(6) {
+            Ok(a) => Ok([r, g, b, a]),
+            _ => Err("colour should be RRGGBB or RRGGBBAA".to_string()),
+        },
+        _ => Err("colour should be RRGGBB or RRGGBBAA".to_string()),
+    }
+}
+
+fn parse_date_format(format_str: &str) -> Result<String, String> {
+    if StrftimeItems::new(format_str).any(|item| item == Item::Error) {
+        Err("invalid date format".to_string())
     } else {
-        Ok(vec![
-            time_str[0..2].parse()?,
-            time_str[2..4].parse()?,
-            time_str[4..6].parse()?,
-        ])
+        Ok(format_str.to_string())
+    }
+}
+
+fn parse_clock_format(format_str: &str) -> Result<String, String> {
+    if StrftimeItems::new(format_str).any(|item| item == Item::Error) {
+        Err("invalid clock format".to_string())
+    } else {
+        Ok(format_str.to_string())
     }
 }
 
@@ -61,19 +421,9 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static
     let mut generator = SrtGenerator::default();
 
     if let Some(after_str) = cli.after {
-        let mut valid = true;
-        let after_time: Vec<u32> = time_to_vec(&after_str)?;
-
-        if after_time.len() == 3 && after_time[0] <= 24 && after_time[1] < 60 || after_time[2] < 60
-        {
-            generator.after_hour(after_time[0]);
-            generator.after_minute(after_time[1]);
-            generator.after_second(after_time[2]);
+        if let Some(after_time) = time_to_delta(&after_str)? {
+            generator.after(after_time);
         } else {
-            valid = false;
-        }
-
-        if !valid {
             // TODO
             eprintln!("after invalid");
             return Ok(());
@@ -81,20 +431,9 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static
     }
 
     if let Some(before_str) = cli.before {
-        let mut valid = true;
-        let before_time: Vec<u32> = time_to_vec(&before_str)?;
-
-        if before_time.len() == 3 && before_time[0] <= 24 && before_time[1] < 60
-            || before_time[2] < 60
-        {
-            generator.before_hour(before_time[0]);
-            generator.before_minute(before_time[1]);
-            generator.before_second(before_time[2]);
+        if let Some(before_time) = time_to_delta(&before_str)? {
+            generator.before(before_time);
         } else {
-            valid = false;
-        }
-
-        if !valid {
             // TODO
             eprintln!("before invalid");
             return Ok(());
@@ -102,92 +441,239 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static
     }
 
     if let Some(start_str) = cli.start {
-        let mut valid = true;
-        let start_time: Vec<u32> = time_to_vec(&start_str)?;
-
-        if start_time.len() == 3 && start_time[0] <= 24 && start_time[1] < 60 || start_time[2] < 60
-        {
-            generator.starting_hour(start_time[0]);
-            generator.starting_minute(start_time[1]);
-            generator.starting_second(start_time[2]);
+        if let Some(start_time) = time_to_delta(&start_str)? {
+            generator.starting(start_time);
         } else {
-            valid = false;
-        }
-
-        if !valid {
             // TODO
             eprintln!("start invalid");
             return Ok(());
         }
     }
 
-    let mut previous_iter_info: Option<(usize, chrono::TimeDelta)> = None;
-    let mut summary = Summary::default();
+    generator.metrics(cli.metric);
+    generator.clock_format(cli.clock_format);
+    for (metric, threshold) in cli.threshold {
+        generator.threshold(metric, threshold);
+    }
+    let mut filters: Vec<(Metric, Vec<Filter>)> = Vec::new();
+    for (metric, filter) in cli.filter {
+        match filters.iter_mut().find(|(m, _)| *m == metric) {
+            Some((_, metric_filters)) => metric_filters.push(filter),
+            None => filters.push((metric, vec![filter])),
+        }
+    }
+    for (metric, metric_filters) in filters {
+        generator.filters(metric, metric_filters);
+    }
+    generator.thermocline(cli.thermocline);
+    generator.smooth_heading(TimeDelta::milliseconds(
+        (cli.heading_smoothing * 1000.0) as i64,
+    ));
+    generator.turnaround(cli.turnaround);
+    generator.events(cli.events);
+    generator.safety_stop(cli.safety_stop.map(|seconds| SafetyStop {
+        duration: TimeDelta::milliseconds((seconds * 1000.0) as i64),
+        ..Default::default()
+    }));
 
-    for fit_file in cli.fit_files.iter() {
-        let iter = if let Some(info) = previous_iter_info {
-            generator.concat(info.0, info.1, fit_file)?
+    if let Some(fps) = cli.fps {
+        if cli.timecode {
+            generator.smpte_timecode(fps);
         } else {
-            generator.open(fit_file)?
+            generator.frame_rate(fps);
+        }
+    }
+
+    if let Some(method) = cli.interpolate {
+        generator.interpolate(method, TimeDelta::microseconds((1e6 / cli.rate) as i64));
+    }
+
+    if cli.no_summary {
+        generator.summary(None);
+    } else {
+        let mut cue = SummaryCue {
+            delay: TimeDelta::milliseconds((cli.summary_delay * 1000.0) as i64),
+            duration: TimeDelta::milliseconds((cli.summary_duration * 1000.0) as i64),
+            position: cli.summary_position,
+            per_file: cli.summary_per_file,
+            ..Default::default()
         };
-        summary = summary.merge(&iter.summary)?;
-        for (count, time_delta, srt) in iter.into_iter() {
-            println!("{srt:}\n");
-            // TODO find other way to keep state of iterator
-            previous_iter_info = Some((count, time_delta));
+        if let Some(template) = cli.summary_template {
+            cue.template = Some(std::fs::read_to_string(template)?);
         }
+        generator.summary(Some(cue));
+    }
+
+    if let Some(path) = cli.dive_sites {
+        let mut sites = if path == "example" {
+            DiveSites::example()
+        } else {
+            DiveSites::open(path)?
+        };
+        sites.tolerance(cli.site_tolerance);
+        generator.dive_sites(Some(sites));
     }
-    if !summary.is_empty() && !cli.no_summary {
-        if let Some((mut count, previous_time)) = previous_iter_info {
-            count += 1;
-            let previous_time = previous_time
-                .checked_add(&chrono::TimeDelta::try_seconds(5).unwrap())
-                .unwrap();
-            let previous_time_str = fit2srt_core::srt_iter::delta_srt_format(&previous_time);
-            let end_time = previous_time
-                .checked_add(&chrono::TimeDelta::try_seconds(10).unwrap())
-                .unwrap();
-            let mut summary_str = "Summary:\n".to_string();
-            if let Some((lat, long)) = summary.location() {
-                summary_str += &format!("Location: {lat:}, {long:}\n");
-            }
-            if let Some(avg_t) = summary.avg_temperature {
-                summary_str += &format!("Temperature: {avg_t:}{}", summary.temp_unit());
-                if let Some(min_t) = summary.min_temperature {
-                    if avg_t != min_t {
-                        summary_str += &format!(" (min: {min_t:}{})", summary.temp_unit());
-                    }
-                }
-                summary_str += "\n";
-            }
-            if let Some(avg_d) = summary.avg_depth {
-                summary_str += &format!("Depth: {avg_d:}{}", summary.depth_unit());
-                if let Some(max_d) = summary.max_depth {
-                    summary_str += &format!(" (max: {max_d:}{})", summary.depth_unit());
-                }
-                summary_str += "\n";
-            }
 
-            println!(
-                "{}\n{} --> {}\n{}",
-                count,
-                previous_time_str,
-                fit2srt_core::srt_iter::delta_srt_format(&end_time),
-                summary_str
+    if let Some(path) = cli.quirks {
+        generator.quirks(Quirks::open(path)?);
+    }
+
+    let locale = |lang: Lang| {
+        let mut locale = Locale::new(lang);
+        locale.decimal_comma = cli.decimal_comma;
+        if let Some(format) = &cli.date_format {
+            locale.date_format = format.clone();
+        }
+        locale
+    };
+    // the exports are in the first language
+    let export_lang = cli.lang.first().copied().unwrap_or_default();
+    let locales: Vec<Locale> = cli.lang.iter().copied().map(locale).collect();
+    // the subtitle of one video in each language
+    let map = VideoMap::new(cli.gap);
+    let mut tracks = generator
+        .concat_locales(&cli.fit_files, &map, &locales)?
+        .into_iter()
+        .map(|mut iters| iters.remove(0));
+    let iter = tracks.next().expect("one track of each language");
+    if let Some(path) = cli.export {
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("kml") => waypoint::kml(&iter.summary.waypoints),
+            _ => waypoint::gpx(&iter.summary.waypoints),
+        };
+        std::fs::write(path, content)?;
+    }
+    if let Some(path) = cli.chapters {
+        let chapters = iter.chapters();
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("ffmetadata" | "ffmeta") => chapter::ffmetadata(&chapters, iter.duration()),
+            Some("vtt") => chapter::webvtt(&chapters, iter.duration()),
+            Some("xml") => chapter::matroska(&chapters, iter.duration(), export_lang),
+            _ => chapter::youtube(&chapters),
+        };
+        std::fs::write(path, content)?;
+    }
+    if cli.profile_png.is_some() || cli.profile_ass.is_some() {
+        let chart = ProfileChart {
+            width: cli.profile_size.0,
+            height: cli.profile_size.1,
+            background: cli.profile_background,
+            ..Default::default()
+        };
+        let rate = cli.fps.unwrap_or("1".parse()?);
+        if let Some(dir) = cli.profile_png {
+            chart.write_frames(iter.profile(), dir, rate)?;
+        }
+        if let Some(path) = cli.profile_ass {
+            // at the bottom left of the video
+            let margin = 20;
+            let position = (
+                margin,
+                cli.video_size.1.saturating_sub(chart.height + margin),
             );
+            let interval = rate.frame_time(1).max(TimeDelta::milliseconds(100));
+            std::fs::write(
+                path,
+                chart.ass(iter.profile(), cli.video_size, position, interval),
+            )?;
+        }
+    }
+    if let (Some(dir), Some(rate)) = (cli.overlay, cli.fps) {
+        let overlay = Overlay {
+            width: cli.video_size.0,
+            height: cli.video_size.1,
+            ..Default::default()
+        };
+        overlay.write_frames(&iter, dir, rate)?;
+    }
+    let narration = cli.subtitle.as_ref().map(subtitle::open).transpose()?;
+    let several = cli.lang.len() > 1;
+    // with the language before the extension if there are several languages
+    let track_path = |path: PathBuf, lang: Lang| {
+        if several {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("srt");
+            path.with_extension(format!("{}.{extension}", lang.code()))
+        } else {
+            path
+        }
+    };
+    for (lang, mut track) in cli
+        .lang
+        .iter()
+        .copied()
+        .zip(std::iter::once(iter).chain(tracks))
+    {
+        if let Some(cues) = &narration {
+            track.merge_subtitle(cues, cli.overlap);
+        }
+        if cli.clip.is_empty() {
+            let output = cli.output.clone().or_else(|| {
+                let first = cli.fit_files.first().filter(|_| several)?;
+                Some(first.with_extension("srt"))
+            });
+            match output {
+                Some(path) => write_srt(&mut File::create(track_path(path, lang))?, track)?,
+                None => write_srt(&mut std::io::stdout(), track)?,
+            }
+        } else {
+            let clips: Vec<Clip> = cli.clip.iter().map(|c| c.clip).collect();
+            for (i, (clip_iter, clip_arg)) in
+                track.split(&clips)?.into_iter().zip(&cli.clip).enumerate()
+            {
+                let output = clip_arg
+                    .output
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(format!("clip-{}.srt", i + 1)));
+                let mut fp = File::create(track_path(output, lang))?;
+                write_srt(&mut fp, clip_iter)?;
+            }
         }
     }
     Ok(())
 }
 
+fn write_srt<W: Write>(out: &mut W, iter: SrtIter) -> std::io::Result<()> {
+    for (_count, _time_delta, srt) in iter {
+        writeln!(out, "{srt:}\n")?;
+    }
+    Ok(())
+}
+
 fn main() {
     if let Err(e) = run(Cli::parse()) {
         println!("{e:?}");
     }
 }
 
+#[test]
+fn test_parse_colour() {
+    assert_eq!(parse_colour("#ff8000"), Ok([255, 128, 0, 255]));
+    assert_eq!(parse_colour("00000080"), Ok([0, 0, 0, 128]));
+    assert!(parse_colour("ff80").is_err());
+}
+
 #[test]
 fn test_time_to_vec() {
     assert_eq!(time_to_vec("15:01:30"), Ok(vec![15, 1, 30]));
     assert_eq!(time_to_vec("150130"), Ok(vec![15, 1, 30]));
+    assert_eq!(time_to_vec("0358"), Ok(vec![]));
+    assert!(time_to_vec("0Ω829").is_err());
+}
+
+#[test]
+fn test_time_to_delta() {
+    let t = TimeDelta::hours(3) + TimeDelta::minutes(58) + TimeDelta::seconds(29);
+    assert_eq!(time_to_delta("03:58:29"), Ok(Some(t)));
+    assert_eq!(
+        time_to_delta("03:58:29.450"),
+        Ok(Some(t + TimeDelta::milliseconds(450)))
+    );
+    assert_eq!(
+        time_to_delta("035829,45"),
+        Ok(Some(t + TimeDelta::milliseconds(450)))
+    );
+    assert_eq!(time_to_delta("03:61:29"), Ok(None));
+    assert_eq!(time_to_delta("0358"), Ok(None));
+    assert!(time_to_delta("03:10:00.5€").is_err());
+    assert_eq!(time_to_delta("03:10:00.+5"), Ok(None));
 }
@@ -53,15 +53,20 @@ fn with_time_slot() {
 1.7m
 
 2
-00:00:01,000 --> 00:00:02,000
+00:00:01,000 --> 00:00:04,000
 2.0m
 
 3
-00:00:06,000 --> 00:00:16,000
+00:00:09,000 --> 00:00:19,000
 Summary:
-Location: 21.939215641468763, 120.74536473490298
-Temperature: 31C
+Date: 2024-09-07 03:10 (#19)
+Location: 21.939216, 120.745365
+Dive time: 31:18
 Depth: 4.248m (max: 8.908m)
+Temperature: 31C
+Gas: Air
+Surface interval: 23:54:21 (residual N2: 3%)
+Device: garmin descent_g1_asia (3463803905)
 
 "#
     );
@@ -81,6 +86,118 @@ fn with_start_time() {
     ));
 }
 
+#[test]
+fn with_timecode() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["--fps", "25", "--timecode", "../assets/garmin_g1.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(output
+        .stdout
+        .starts_with(b"1\n00:00:00:00 --> 00:00:01:00\n1.5m\n\n"));
+}
+
+#[test]
+fn with_interpolation() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["--interpolate", "linear", "../assets/garmin_g1.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(output.stdout.starts_with(
+        b"1\n00:00:00,000 --> 00:00:00,750\n1.5m\n\n2\n00:00:00,750 --> 00:00:01,250\n1.6m\n\n"
+    ));
+
+    for rate in ["0", "1000"] {
+        let output = test_bin::get_test_bin("fit2srt-cli")
+            .args([
+                "--interpolate",
+                "linear",
+                "--rate",
+                rate,
+                "../assets/garmin_g1.fit",
+            ])
+            .output()
+            .expect("Failed to launch fit2srt");
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+
+    // the interpolated values move more than the threshold
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-n",
+            "--interpolate",
+            "linear",
+            "--threshold",
+            "depth=0.5",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(output.stdout.starts_with(
+        b"1\n00:00:00,000 --> 00:00:02,500\n1.9m\n\n2\n00:00:02,500 --> 00:00:12,250\n1.3m\n\n3\n00:00:12,250 --> 00:00:25,000\n0.7m\n\n"
+    ));
+}
+
+// Run test in UTC timezone and in CI
+#[test_with::timezone(0)]
+fn with_millisecond_start_time() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["-s", "03:09:59.250", "../assets/garmin_g1.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+
+    assert!(output
+        .stdout
+        .starts_with(b"1\n00:00:00,750 --> 00:00:01,750\n1.5m\n\n"));
+}
+
+// Run test in UTC timezone and in CI
+#[test_with::timezone(0)]
+fn with_frame_rate() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-s",
+            "03:09:59.250",
+            "--fps",
+            "29.97",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+
+    assert!(output
+        .stdout
+        .starts_with(b"1\n00:00:00,735 --> 00:00:01,736\n1.5m\n\n"));
+}
+
+// Run test in UTC timezone and in CI
+#[test_with::timezone(0)]
+fn with_clips() {
+    let dir = std::env::temp_dir();
+    let first = dir.join("fit2srt-clip-1.srt");
+    let second = dir.join("fit2srt-clip-2.srt");
+    test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-n",
+            "--clip",
+            &format!("03:10:00+00:00:03={}", first.display()),
+            "--clip",
+            &format!("03:10:03+00:00:05={}", second.display()),
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+
+    assert_eq!(
+        std::fs::read_to_string(first).unwrap(),
+        "1\n00:00:00,000 --> 00:00:01,000\n1.5m\n\n2\n00:00:01,000 --> 00:00:02,000\n1.7m\n\n3\n00:00:02,000 --> 00:00:03,000\n2.0m\n\n"
+    );
+    assert!(std::fs::read_to_string(second)
+        .unwrap()
+        .starts_with("1\n00:00:00,000 --> 00:00:03,000\n1.8m\n\n"));
+}
+
 #[test_with::timezone(0)]
 fn concat() {
     // starting time 151534
@@ -96,6 +213,578 @@ fn concat() {
         .expect("Failed to launch fit2srt");
 
     assert!(output.stdout.ends_with(
-        b"381\n00:18:33,000 --> 00:02:27,000\n0.5m\n\n382\n00:18:34,000 --> 00:02:29,000\n0.3m\n\n"
+        b"381\n00:19:36,000 --> 00:19:37,000\n0.5m\n\n382\n00:19:37,000 --> 00:19:39,000\n0.3m\n\n"
+    ));
+}
+
+#[test_with::timezone(0)]
+fn summary_per_file() {
+    // the files are ordered by the start times instead of the arguments
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-a",
+            "07:15:34",
+            "--summary-per-file",
+            "--summary-position",
+            "start",
+            "../assets/131-2.fit",
+            "../assets/131-1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first = stdout.find("Date: 2025-08-14 07:05 (#108)\nDive of the day: 1\n");
+    let second = stdout.find("Date: 2025-08-14 07:32 (#109)\nDive of the day: 2\n");
+    assert!(first.is_some() && first < second);
+    assert!(stdout.contains("Surface interval: 1:32 (residual N2: 11%)\n"));
+}
+
+#[test_with::timezone(0)]
+fn concat_without_gap() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-a",
+            "07:15:34",
+            "-n",
+            "--gap",
+            "remove",
+            "../assets/131-1.fit",
+            "../assets/131-2.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+
+    assert!(output.stdout.ends_with(
+        b"381\n00:19:07,000 --> 00:19:08,000\n0.5m\n\n382\n00:19:08,000 --> 00:19:10,000\n0.3m\n\n"
+    ));
+}
+
+#[test_with::timezone(0)]
+fn with_summary_options() {
+    let dir = std::env::temp_dir();
+    let template = dir.join("fit2srt-summary-template.txt");
+    std::fs::write(&template, "Dive #{dive_number}[ at {location}]\n{unknown}").unwrap();
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-a",
+            "03:10:00",
+            "-b",
+            "03:10:05",
+            "--summary-template",
+            template.to_str().unwrap(),
+            "--summary-delay",
+            "0.5",
+            "--summary-duration",
+            "3",
+            "--summary-position",
+            "both",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let summary = "Dive #19 at 21.939216, 120.745365";
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("1\n00:00:00,000 --> 00:00:01,000\n1.7m\n\n2\n00:00:00,500 --> 00:00:03,500\n{summary}\n\n3\n00:00:01,000 --> 00:00:04,000\n2.0m\n\n4\n00:00:04,500 --> 00:00:07,500\n{summary}\n\n")
+    );
+
+    for arg in [
+        "--summary-delay=-1",
+        "--summary-delay=1e300",
+        "--summary-duration=-5",
+        "--summary-duration=0",
+        "--summary-duration=NaN",
+    ] {
+        let output = test_bin::get_test_bin("fit2srt-cli")
+            .args([arg, "../assets/garmin_g1.fit"])
+            .output()
+            .expect("Failed to launch fit2srt");
+        assert!(!output.status.success());
+    }
+}
+
+#[test_with::timezone(0)]
+fn with_quirks() {
+    let quirks = std::env::temp_dir().join("fit2srt-quirks.csv");
+    // the depth is taken as feet for all garmin products but another one
+    std::fs::write(
+        &quirks,
+        "manufacturer,product,field,name,scale,offset,units\ngarmin,,depth,,,,ft\ngarmin,9999,depth,,2,,\n",
+    )
+    .unwrap();
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-n",
+            "-a",
+            "03:10:00",
+            "-b",
+            "03:10:05",
+            "--quirks",
+            quirks.to_str().unwrap(),
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "1\n00:00:00,000 --> 00:00:04,000\n0.6m\n\n"
+    );
+}
+
+#[test]
+fn with_developer_fields() {
+    let template = std::env::temp_dir().join("fit2srt-developer-template.txt");
+    std::fs::write(&template, "Scrubber: {dev:scrubber_time}\nPPO2: {dev:ppo2}").unwrap();
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--metric",
+            "depth,dev:0:ppo2",
+            "--summary-template",
+            template.to_str().unwrap(),
+            "../assets/developer_fields.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(
+        "1\n00:00:00,000 --> 00:00:01,000\n1.5m 0.7bar\n\n2\n00:00:01,000 --> 00:00:02,000\n2.0m 1bar\n\n"
+    ));
+    // the session has no ppo2
+    assert!(stdout.ends_with("\nScrubber: 95min\n\n"));
+}
+
+#[test]
+fn with_filter() {
+    let stdout = |args: &[&str]| {
+        let output = test_bin::get_test_bin("fit2srt-cli")
+            .args(args)
+            .args(["--no-summary", "../assets/garmin_g1.fit"])
+            .output()
+            .expect("Failed to launch fit2srt");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    // the medians of the 5 seconds around the samples of 1.5m, 1.7m, 2.0m, ...
+    assert!(stdout(&["--filter", "depth=median:5"]).starts_with(
+        "1\n00:00:00,000 --> 00:00:01,000\n1.6m\n\n2\n00:00:01,000 --> 00:00:03,000\n1.8m\n\n3\n00:00:03,000 --> 00:00:05,000\n1.9m\n\n"
+    ));
+    // the depth of the safety stop is filtered too
+    assert!(stdout(&["--safety-stop"]).contains("Safety stop"));
+    assert!(
+        !stdout(&["--safety-stop", "--filter", "depth=max_rate:0.001"]).contains("Safety stop")
+    );
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["--filter", "depth=kalman:1", "../assets/garmin_g1.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(!output.status.success());
+}
+
+#[test_with::timezone(0)]
+fn with_ccr() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--metric",
+            "ppo2,setpoint",
+            "--events=setpoint_switch,bailout",
+            "../assets/ccr.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("1\n00:00:00,000 --> 00:00:03,000\nPPO2 1.00 SP 1.20\n\n"));
+    // the setpoints of `dive_settings` are after the events in the file
+    assert!(stdout.contains("\n00:00:03,000 --> 00:00:08,000\nSetpoint high: 1.20\n\n"));
+    assert!(stdout.contains("\n00:00:07,000 --> 00:00:08,000\nPPO2 0.21 OC\n\n"));
+    assert!(stdout.contains("\n00:00:08,000 --> 00:00:13,000\n⚠ Bailout to open circuit\n\n"));
+    assert!(stdout.contains("\n00:00:10,000 --> 00:00:15,000\nBack on the loop\n\n"));
+    assert!(!stdout.contains("PPO2 warning"));
+    assert!(stdout.contains("\nSetpoints: OC 0:02, 0.60 0:03, 1.20 0:06 (PPO2: 0.21 - 1.35)\n"));
+
+    // the time on the setpoints is of the window like the PPO2
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["-a", "01:46:45", "--metric", "ppo2", "../assets/ccr.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("\nSetpoints: OC 0:02, 1.20 0:03 (PPO2: 0.21 - 1.35)\n"));
+}
+
+#[test_with::timezone(0)]
+fn with_dive_sites() {
+    let sites = std::env::temp_dir().join("fit2srt-dive-sites.csv");
+    std::fs::write(
+        &sites,
+        "name,latitude,longitude\n\"Houbihu, Kenting\",21.9397,120.7449\n",
+    )
+    .unwrap();
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-a",
+            "03:10:00",
+            "-b",
+            "03:10:05",
+            "--dive-sites",
+            sites.to_str().unwrap(),
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\nLocation: Houbihu, Kenting\n"));
+
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-a",
+            "03:10:00",
+            "-b",
+            "03:10:05",
+            "--dive-sites",
+            sites.to_str().unwrap(),
+            "--site-tolerance",
+            "10",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\nLocation: 21.939216, 120.745365\n"));
+}
+
+#[test_with::timezone(0)]
+fn export_waypoints() {
+    let gpx = std::env::temp_dir().join("fit2srt-waypoints.gpx");
+    test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--export",
+            gpx.to_str().unwrap(),
+            "../assets/garmin_g1.fit",
+            "../assets/713-2.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let gpx = std::fs::read_to_string(gpx).unwrap();
+    assert!(gpx.contains("<name>Dive #19 entry</name>"));
+    assert!(gpx.contains("<name>Dive #19 exit</name>"));
+    // 713-2.fit has no position
+    assert_eq!(gpx.matches("<wpt ").count(), 2);
+}
+
+#[test_with::timezone(0)]
+fn with_heart_rate() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["--metric", "depth,heart_rate", "../assets/713-2.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(
+        "1\n00:00:00,000 --> 00:00:02,000\n1.5m 84bpm\n\n2\n00:00:02,000 --> 00:00:03,000\n1.3m 84bpm\n\n"
+    ));
+    assert!(stdout.contains("\nHeart rate: 89bpm (max: 114bpm)\n"));
+}
+
+#[test_with::timezone(0)]
+fn with_temperature() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-n",
+            "--metric",
+            "temperature",
+            "--thermocline",
+            "0.1",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(
+        "1\n00:00:00,000 --> 00:01:49,000\n31C\n\n2\n00:00:00,000 --> 00:00:05,000\nThermocline: 1.0m (-0.2C)\n\n3\n00:01:49,000 --> 00:02:25,000\n32C\n\n"
+    ));
+
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-n",
+            "--metric",
+            "temperature",
+            "--threshold",
+            "temperature=1",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    // no change is more than the threshold, the first value lasts to the last sample
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "1\n00:00:00,000 --> 00:31:18,000\n32C\n\n"
+    );
+}
+
+#[test]
+fn with_profile_overlay() {
+    let dir = std::env::temp_dir().join("fit2srt-profile");
+    let _ = std::fs::remove_dir_all(&dir);
+    let ass = std::env::temp_dir().join("fit2srt-profile.ass");
+    test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--profile-png",
+            dir.to_str().unwrap(),
+            "--profile-ass",
+            ass.to_str().unwrap(),
+            "-a",
+            "03:10:00",
+            "-b",
+            "03:12:00",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    // a frame per second
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 120);
+    assert!(dir.join("profile-00000.png").exists());
+    let ass = std::fs::read_to_string(ass).unwrap();
+    assert!(ass.starts_with("[Script Info]\nScriptType: v4.00+\nPlayResX: 1920\nPlayResY: 1080\n"));
+    assert!(ass.contains(",Profile,,0,0,0,,{\\pos(20,940)}{\\p1}m 8 8 l "));
+}
+
+#[test]
+fn with_overlay() {
+    let dir = std::env::temp_dir().join("fit2srt-overlay");
+    let _ = std::fs::remove_dir_all(&dir);
+    test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--overlay",
+            dir.to_str().unwrap(),
+            "--fps",
+            "25",
+            "--video-size",
+            "320x180",
+            "-a",
+            "03:10:00",
+            "-b",
+            "03:10:10",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    // frames at 25 fps in the window
+    assert!(std::fs::read_dir(&dir).unwrap().count() >= 225);
+    assert!(dir.join("overlay-00000.png").exists());
+}
+
+#[test_with::timezone(0)]
+fn with_events() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["-n", "--events=ascent_rate", "../assets/garmin_g1.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // overlapped with the depth cues
+    assert!(stdout.contains(
+        "60\n00:02:19,000 --> 00:02:20,000\n0.5m\n\n61\n00:02:19,000 --> 00:02:24,000\n⚠ Ascent rate\n\n"
+    ));
+    assert_eq!(stdout.matches("⚠ Ascent rate").count(), 3);
+
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["-n", "--events=gas_switch", "../assets/garmin_g1.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains('⚠'));
+}
+
+#[test]
+fn with_chapters() {
+    let vtt = std::env::temp_dir().join("fit2srt-chapters.vtt");
+    test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--chapters",
+            vtt.to_str().unwrap(),
+            "--gap",
+            "remove",
+            "../assets/garmin_g1.fit",
+            "../assets/713-2.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let vtt = std::fs::read_to_string(vtt).unwrap();
+    assert!(vtt.starts_with("WEBVTT\n\n1\n00:00:00.000 --> "));
+    assert!(vtt.contains("\nLap 1\n\n2\n"));
+    assert!(vtt.ends_with("\nLap 2\n"));
+}
+
+#[test]
+fn with_subtitle() {
+    let vtt = std::env::temp_dir().join("fit2srt-narration.vtt");
+    std::fs::write(
+        &vtt,
+        "WEBVTT\n\n00:01.000 --> 00:04.000\nHere is the wreck\n\n00:06.000 --> 00:07.500\nLook up\n",
+    )
+    .unwrap();
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--no-summary",
+            "--subtitle",
+            vtt.to_str().unwrap(),
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with(
+        "1\n00:00:00,000 --> 00:00:01,000\n1.5m\n\n2\n00:00:01,000 --> 00:00:02,000\n1.7m\nHere is the wreck\n\n"
     ));
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--no-summary",
+            "--subtitle",
+            vtt.to_str().unwrap(),
+            "--overlap",
+            "position",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with(
+        "1\n00:00:00,000 --> 00:00:01,000\n{\\an8}1.5m\n\n2\n00:00:01,000 --> 00:00:04,000\nHere is the wreck\n\n"
+    ));
+}
+
+#[test_with::timezone(0)]
+fn with_lang() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--lang",
+            "zh",
+            "--decimal-comma",
+            "--metric",
+            "depth,dive_time",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("1\n00:00:00,000 --> 00:00:01,000\n1,5m 潛水時間 0:01\n\n"));
+    assert!(stdout.contains("\n潛水摘要：\n日期：2024年9月7日 03:10 (#19)\n"));
+    assert!(stdout.contains("\n氣體：空氣\n"));
+
+    let dir = std::env::temp_dir().join("fit2srt-lang");
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "--lang",
+            "en,ja",
+            "--output",
+            dir.join("dive.srt").to_str().unwrap(),
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(output.stdout.is_empty());
+    let en = std::fs::read_to_string(dir.join("dive.en.srt")).unwrap();
+    assert!(en.contains("\nSummary:\nDate: 2024-09-07 03:10 (#19)\n"));
+    let ja = std::fs::read_to_string(dir.join("dive.ja.srt")).unwrap();
+    assert!(ja.contains("\nダイブサマリー：\n日付：2024年9月7日 03:10 (#19)\n"));
+    assert!(!dir.join("dive.zh.srt").exists());
+}
+
+#[test_with::timezone(0)]
+fn with_safety_stop() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["-n", "--safety-stop", "../assets/garmin_g1.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\n00:24:43,000 --> 00:24:44,000\nSafety stop 3:00\n\n"));
+    assert!(stdout.contains("\n00:24:44,000 --> 00:24:45,000\nSafety stop 2:59\n\n"));
+
+    for seconds in ["0", "-180", "NaN"] {
+        let output = test_bin::get_test_bin("fit2srt-cli")
+            .args([
+                "-n",
+                &format!("--safety-stop={seconds}"),
+                "../assets/garmin_g1.fit",
+            ])
+            .output()
+            .expect("Failed to launch fit2srt");
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+}
+
+#[test_with::timezone(0)]
+fn with_time_fields() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-n",
+            "--metric",
+            "depth,dive_time,clock",
+            "--clock-format",
+            "%H:%M:%S",
+            "../assets/garmin_g1.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(
+        "1\n00:00:00,000 --> 00:00:01,000\n1.5m Dive time 0:01 03:10:01\n\n2\n00:00:01,000 --> 00:00:02,000\n1.7m Dive time 0:02 03:10:02\n\n"
+    ));
+}
+
+#[test]
+fn with_heading() {
+    // the compass swings over the north then points to the south at 30s
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-n",
+            "--metric",
+            "heading",
+            "--turnaround",
+            "90",
+            "../assets/compass.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "1\n00:00:00,000 --> 00:00:29,000\nN 355°\n\n2\n00:00:29,000 --> 00:00:59,000\nS 180°\n\n3\n00:00:30,000 --> 00:00:35,000\nTurn around\n\n"
+    );
+
+    // no turn of 270 degrees
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args([
+            "-n",
+            "--metric",
+            "depth",
+            "--turnaround",
+            "270",
+            "../assets/compass.fit",
+        ])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Turn around"));
+
+    for seconds in ["-5", "inf", "NaN"] {
+        let output = test_bin::get_test_bin("fit2srt-cli")
+            .args([
+                "--metric",
+                "heading",
+                &format!("--heading-smoothing={seconds}"),
+                "../assets/compass.fit",
+            ])
+            .output()
+            .expect("Failed to launch fit2srt");
+        assert!(!output.status.success());
+    }
+}
+
+#[test_with::timezone(0)]
+fn with_tank_pressure() {
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .arg("../assets/tank.fit")
+        .output()
+        .expect("Failed to launch fit2srt");
+    // the transmitter is over the tank summary of 210bar → 150bar
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\nTank: 200bar → 160bar\n"));
+
+    let output = test_bin::get_test_bin("fit2srt-cli")
+        .args(["-b", "01:46:55", "../assets/tank.fit"])
+        .output()
+        .expect("Failed to launch fit2srt");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\nTank: 200bar → 180bar\n"));
 }
@@ -1,11 +1,12 @@
 use std::fs::write;
 use std::path::PathBuf;
 
-use chrono::{NaiveTime, TimeDelta, Timelike};
-use fit2srt_core::SrtGenerator;
+use chrono::{NaiveTime, TimeDelta};
+use fit2srt_core::{DiveSites, Lang, Locale, SrtGenerator};
 // use iced::widget::qr_code::{Data, QRCode};
 use iced::widget::{
-    button, column, container, horizontal_space, image, rich_text, row, scrollable, span, text,
+    button, column, container, horizontal_space, image, pick_list, rich_text, row, scrollable,
+    span, text,
 };
 use iced::widget::{Button, Column};
 use iced::{color, font::Weight, Color, Element, Fill, Font};
@@ -33,7 +34,9 @@ pub struct App {
     screen: Screen,
     debug: bool,
     fitfile: Option<PathBuf>,
+    dive_sites: Option<PathBuf>,
     starting_time: NaiveTime,
+    locale: Locale,
     // btc_qr_data: Data,
     // paypal_qr_data: Data,
 }
@@ -43,17 +46,19 @@ pub enum Message {
     BackPressed,
     NextPressed,
     SelectFile,
+    SelectDiveSites,
     StartingTimeChange(i64),
+    LangSelected(Lang),
 }
 
 impl App {
     fn title(&self) -> String {
-        let screen = match self.screen {
+        let screen = self.locale.text(match self.screen {
             Screen::Intro => "Introduction",
             Screen::Input => "Setup inputs",
             Screen::End => "End",
             Screen::CryptoDonate => "CryptoDonate",
-        };
+        });
 
         format!("Fit2srt - {screen}")
     }
@@ -87,15 +92,32 @@ impl App {
 
                 self.fitfile = path.unwrap_or_default();
             }
+            Message::SelectDiveSites => {
+                #[cfg(not(target_arch = "wasm32"))]
+                let path = DialogBuilder::file()
+                    .set_location("~/")
+                    .add_filter("Dive sites", ["csv", "geojson", "json"])
+                    .open_single_file()
+                    .show();
+
+                self.dive_sites = path.unwrap_or_default();
+            }
             Message::StartingTimeChange(t) => {
                 self.starting_time += TimeDelta::try_seconds(t).unwrap();
             }
+            Message::LangSelected(lang) => {
+                self.locale = Locale::new(lang);
+            }
         }
     }
 
     fn gen_srt(&self) -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static>> {
         let mut generator = SrtGenerator::default();
-        generator.after_second(self.starting_time.num_seconds_from_midnight());
+        generator.after(self.starting_time - NaiveTime::MIN);
+        generator.locale(self.locale.clone());
+        if let Some(path) = &self.dive_sites {
+            generator.dive_sites(Some(DiveSites::open(path)?));
+        }
         if let Some(f) = &self.fitfile {
             let mut srt_content = String::new();
             for (_, _, srt) in generator.open(f)? {
@@ -115,17 +137,17 @@ impl App {
     }
 
     fn view(&self) -> Element<'_, Message> {
-        let controls = row![]
-            .push_maybe(self.screen.previous().is_some().then(|| {
-                padded_button("Back")
-                    .on_press(Message::BackPressed)
-                    .style(button::secondary)
-            }))
-            .push(horizontal_space())
-            .push_maybe(
-                self.can_continue()
-                    .then(|| padded_button("Next").on_press(Message::NextPressed)),
-            );
+        let controls =
+            row![]
+                .push_maybe(self.screen.previous().is_some().then(|| {
+                    padded_button(self.locale.text("Back"))
+                        .on_press(Message::BackPressed)
+                        .style(button::secondary)
+                }))
+                .push(horizontal_space())
+                .push_maybe(self.can_continue().then(|| {
+                    padded_button(self.locale.text("Next")).on_press(Message::NextPressed)
+                }));
 
         let screen = match self.screen {
             Screen::Intro => self.welcome(),
@@ -162,28 +184,44 @@ impl App {
     }
 
     fn welcome(&self) -> Column<'_, Message> {
-        Self::container("Welcome!")
+        Self::container(self.locale.text("Welcome!"))
+            .push(pick_list(
+                Lang::ALL,
+                Some(self.locale.lang),
+                Message::LangSelected,
+            ))
             .push(column![
-                text("This is a simple tool for you to make your diving log as video subtitles."),
-                text("You can see some sample video here:"),
+                text(self.locale.text(
+                    "This is a simple tool for you to make your diving log as video subtitles."
+                )),
+                text(self.locale.text("You can see some sample video here:")),
                 rich_text![span("https://www.youtube.com/@yanganto/videos").color(color!(0x0000FF))]
             ])
             // TODO: https://github.com/squidowl/halloy/blob/main/src/widget/selectable_rich_text.rs
             .push(column![
-                text("If you want to any scuba diving crouse, please contact with me."),
+                text(
+                    self.locale
+                        .text("If you want to any scuba diving crouse, please contact with me.")
+                ),
                 rich_text![span("yanganto@gmail.com").color(color!(0x0000FF))]
             ])
     }
 
     fn inputs(&self) -> Column<'_, Message> {
-        Self::container("Setup inputs")
+        Self::container(self.locale.text("Setup inputs"))
             .push(text(if let Some(f) = &self.fitfile {
-                format!("1. Fit file loaded: {}", f.display())
+                format!(
+                    "{} {}",
+                    self.locale.text("1. Fit file loaded:"),
+                    f.display()
+                )
             } else {
-                "1. Select the fit file from you diving computer.".to_string()
+                self.locale
+                    .text("1. Select the fit file from you diving computer.")
+                    .to_string()
             }))
-            .push(padded_button("Open").on_press(Message::SelectFile))
-            .push("2. Setup the starting time of the video")
+            .push(padded_button(self.locale.text("Open")).on_press(Message::SelectFile))
+            .push(self.locale.text("2. Setup the starting time of the video"))
             .push(
                 row![
                     button("+").on_press(Message::StartingTimeChange(3600)),
@@ -201,13 +239,27 @@ impl App {
                 ]
                 .spacing(10),
             )
+            .push(text(if let Some(f) = &self.dive_sites {
+                format!(
+                    "{} {}",
+                    self.locale.text("3. Dive sites loaded:"),
+                    f.display()
+                )
+            } else {
+                self.locale
+                    .text(
+                        "3. Name the location with your dive sites, a CSV or a GeoJSON (optional)",
+                    )
+                    .to_string()
+            }))
+            .push(padded_button(self.locale.text("Open")).on_press(Message::SelectDiveSites))
     }
 
     fn end(&self) -> Column<'_, Message> {
-        Self::container("All Done!")
-        .push(text(format!("The .srt file is created: {}", self.srt_file().unwrap().display())))
-        .push("You can upload .srt to youtube or use it in video editor.")
-        .push("If you like this project, please buy me a coffee via paypal or bitcoin to support me.")
+        Self::container(self.locale.text("All Done!"))
+        .push(text(format!("{} {}", self.locale.text("The .srt file is created:"), self.srt_file().unwrap().display())))
+        .push(self.locale.text("You can upload .srt to youtube or use it in video editor."))
+        .push(self.locale.text("If you like this project, please buy me a coffee via paypal or bitcoin to support me."))
         .push(
             rich_text![span("PayPal").font(Font { weight: Weight::Bold, ..Font::default() })]
         )
@@ -218,9 +270,9 @@ impl App {
         )
     }
     fn crypto_donate(&self) -> Column<'_, Message> {
-        Self::container("Help us")
-            .push("If you want to donate with crypto.")
-            .push("Please help us with Bitcoin.")
+        Self::container(self.locale.text("Help us"))
+            .push(self.locale.text("If you want to donate with crypto."))
+            .push(self.locale.text("Please help us with Bitcoin."))
             .push(rich_text![span("BTC").font(Font {
                 weight: Weight::Bold,
                 ..Font::default()
@@ -294,7 +346,9 @@ impl Default for App {
             screen: Screen::Intro,
             debug: false,
             fitfile: None,
+            dive_sites: None,
             starting_time: NaiveTime::default(),
+            locale: Locale::default(),
             // btc_qr_data: Data::new(BTC_ADDR).unwrap(),
             // paypal_qr_data: Data::new(PAYPAL_ADDR).unwrap(),
         }
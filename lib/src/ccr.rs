@@ -0,0 +1,177 @@
+use chrono::{DateTime, Local};
+
+use crate::locale::Locale;
+use crate::summary::duration_format;
+
+/// A change of the loop of a rebreather from the `dive_alert` of `event` messages
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LoopChange {
+    LowSetpoint,
+    HighSetpoint,
+    // Bailout
+    OpenCircuit,
+    ClosedCircuit,
+}
+
+impl LoopChange {
+    pub fn from_dive_alert(dive_alert: &str) -> Option<Self> {
+        match dive_alert {
+            "setpoint_switch_auto_low" | "setpoint_switch_manual_low" => Some(Self::LowSetpoint),
+            "setpoint_switch_auto_high" | "setpoint_switch_manual_high" => Some(Self::HighSetpoint),
+            "switched_to_open_circuit" => Some(Self::OpenCircuit),
+            "switched_to_closed_circuit" => Some(Self::ClosedCircuit),
+            _ => None,
+        }
+    }
+}
+
+/// The setpoint in bar from the start and after each change, 0 on the open circuit,
+/// the loop starts on the low setpoint and goes back to the last setpoint after a bailout
+pub fn setpoints(
+    start: DateTime<Local>,
+    changes: &[(DateTime<Local>, LoopChange)],
+    low: f64,
+    high: f64,
+) -> Vec<(DateTime<Local>, f64)> {
+    let mut steps = vec![(start, low)];
+    let mut setpoint = low;
+    for (time, change) in changes {
+        let value = match change {
+            LoopChange::LowSetpoint => {
+                setpoint = low;
+                low
+            }
+            LoopChange::HighSetpoint => {
+                setpoint = high;
+                high
+            }
+            LoopChange::OpenCircuit => 0.0,
+            LoopChange::ClosedCircuit => setpoint,
+        };
+        steps.push((*time, value));
+    }
+    steps
+}
+
+/// The setpoint at the time
+pub fn setpoint_at(steps: &[(DateTime<Local>, f64)], time: &DateTime<Local>) -> Option<f64> {
+    let idx = steps.partition_point(|(t, _)| t <= time);
+    idx.checked_sub(1).map(|idx| steps[idx].1)
+}
+
+/// The seconds on each setpoint from the start to the end, ordered by the setpoint
+pub fn time_on_setpoints(
+    steps: &[(DateTime<Local>, f64)],
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Vec<(f64, f64)> {
+    let mut times: Vec<(f64, f64)> = Vec::new();
+    for (i, (step_start, setpoint)) in steps.iter().enumerate() {
+        let step_end = steps.get(i + 1).map_or(end, |(t, _)| *t).min(end);
+        let secs = (step_end - (*step_start).max(start)).num_milliseconds() as f64 / 1000.0;
+        if secs <= 0.0 {
+            continue;
+        }
+        add_time(&mut times, *setpoint, secs);
+    }
+    times
+}
+
+/// Add the seconds on a setpoint, and keep them ordered by the setpoint
+pub(crate) fn add_time(times: &mut Vec<(f64, f64)>, setpoint: f64, secs: f64) {
+    match times.iter_mut().find(|(s, _)| (*s - setpoint).abs() < 1e-6) {
+        Some((_, t)) => *t += secs,
+        None => {
+            times.push((setpoint, secs));
+            times.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+    }
+}
+
+/// `1.30` or `OC` for the open circuit
+pub fn setpoint_name(setpoint: f64) -> String {
+    if setpoint > 0.0 {
+        format!("{setpoint:.2}")
+    } else {
+        "OC".to_string()
+    }
+}
+
+/// `OC 2:00, 0.70 5:00, 1.30 25:00`
+pub(crate) fn setpoints_format(times: &[(f64, f64)], locale: &Locale) -> String {
+    times
+        .iter()
+        .map(|(setpoint, secs)| {
+            format!(
+                "{} {}",
+                locale.number(locale.text(&setpoint_name(*setpoint))),
+                duration_format(secs.round() as i64)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[test]
+fn loop_setpoints() {
+    use chrono::{TimeDelta, TimeZone};
+    let start = Local.with_ymd_and_hms(2024, 9, 7, 9, 0, 0).unwrap();
+    let at = |m: i64| start + TimeDelta::minutes(m);
+    let changes = [
+        (at(2), LoopChange::HighSetpoint),
+        (at(20), LoopChange::OpenCircuit),
+        (at(22), LoopChange::ClosedCircuit),
+        (at(25), LoopChange::LowSetpoint),
+    ];
+    let steps = setpoints(start, &changes, 0.7, 1.3);
+    assert_eq!(setpoint_at(&steps, &at(1)), Some(0.7));
+    assert_eq!(setpoint_at(&steps, &at(21)), Some(0.0));
+    // back on the high setpoint after the bailout
+    assert_eq!(setpoint_at(&steps, &at(23)), Some(1.3));
+    assert_eq!(setpoint_at(&steps, &(start - TimeDelta::seconds(1))), None);
+    let times = time_on_setpoints(&steps, start, at(30));
+    assert_eq!(times, vec![(0.0, 120.0), (0.7, 420.0), (1.3, 1260.0)]);
+    // the steps are cut at the start of the window
+    assert_eq!(
+        time_on_setpoints(&steps, at(10), at(21)),
+        vec![(0.0, 60.0), (1.3, 600.0)]
+    );
+    assert_eq!(
+        setpoints_format(&times, &Locale::default()),
+        "OC 2:00, 0.70 7:00, 1.30 21:00"
+    );
+}
+
+#[test]
+fn loop_changes_of_dive_alerts() -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static>> {
+    let mut fp = std::fs::File::open("../assets/ccr.fit")?;
+    let alerts: Vec<String> = fitparser::from_reader(&mut fp)?
+        .iter()
+        .flat_map(|record| record.fields())
+        .filter(|field| field.name() == "dive_alert")
+        .map(|field| field.value().to_string())
+        .collect();
+    assert_eq!(
+        alerts,
+        [
+            "setpoint_switch_auto_high",
+            "po2_warn",
+            "switched_to_open_circuit",
+            "switched_to_closed_circuit"
+        ]
+    );
+    let changes: Vec<Option<LoopChange>> = alerts
+        .iter()
+        .map(|alert| LoopChange::from_dive_alert(alert))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            Some(LoopChange::HighSetpoint),
+            None,
+            Some(LoopChange::OpenCircuit),
+            Some(LoopChange::ClosedCircuit)
+        ]
+    );
+    Ok(())
+}
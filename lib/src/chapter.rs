@@ -0,0 +1,153 @@
+use chrono::TimeDelta;
+
+use crate::locale::Lang;
+use crate::waypoint::escape;
+
+/// A chapter of the video from a lap or a bookmark of the diver
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chapter {
+    // The time from the beginning of the video
+    pub start: TimeDelta,
+    pub title: String,
+}
+
+/// The chapters with the ends, a chapter ends at the start of the next one
+fn with_ends(chapters: &[Chapter], end: TimeDelta) -> Vec<(&Chapter, TimeDelta)> {
+    chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            let chapter_end = chapters.get(i + 1).map_or(end, |next| next.start);
+            (chapter, chapter_end.max(chapter.start))
+        })
+        .collect()
+}
+
+/// `01:02:03.004`
+fn hms(t: &TimeDelta) -> String {
+    let ms = t.num_milliseconds().max(0);
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+/// The timestamps for a YouTube description, the first chapter should be at 0:00
+pub fn youtube(chapters: &[Chapter]) -> String {
+    let mut lines = Vec::new();
+    if chapters.first().is_none_or(|c| c.start.num_seconds() > 0) {
+        lines.push("0:00 Start".to_string());
+    }
+    for chapter in chapters {
+        let s = chapter.start.num_seconds().max(0);
+        let time = if s >= 3600 {
+            format!("{}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60)
+        } else {
+            format!("{}:{:02}", s / 60, s % 60)
+        };
+        lines.push(format!("{time} {}", chapter.title));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// The metadata file for `ffmpeg -i video -i chapters -map_metadata 1`
+pub fn ffmetadata(chapters: &[Chapter], end: TimeDelta) -> String {
+    let mut metadata = ";FFMETADATA1\n".to_string();
+    for (chapter, chapter_end) in with_ends(chapters, end) {
+        let mut title = String::new();
+        for c in chapter.title.chars() {
+            if matches!(c, '=' | ';' | '#' | '\\') {
+                title.push('\\');
+            }
+            title.push(c);
+        }
+        metadata += &format!(
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={title}\n",
+            chapter.start.num_milliseconds(),
+            chapter_end.num_milliseconds()
+        );
+    }
+    metadata
+}
+
+/// The WebVTT chapters for `<track kind="chapters">`
+pub fn webvtt(chapters: &[Chapter], end: TimeDelta) -> String {
+    let mut vtt = "WEBVTT\n".to_string();
+    for (i, (chapter, chapter_end)) in with_ends(chapters, end).into_iter().enumerate() {
+        vtt += &format!(
+            "\n{}\n{} --> {}\n{}\n",
+            i + 1,
+            hms(&chapter.start),
+            hms(&chapter_end),
+            chapter.title
+        );
+    }
+    vtt
+}
+
+/// The Matroska chapters XML for `mkvmerge --chapters`, the titles are in the language
+pub fn matroska(chapters: &[Chapter], end: TimeDelta, lang: Lang) -> String {
+    let mut xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE Chapters SYSTEM "matroskachapters.dtd">
+<Chapters>
+  <EditionEntry>
+"#
+    .to_string();
+    for (chapter, chapter_end) in with_ends(chapters, end) {
+        xml += "    <ChapterAtom>\n";
+        xml += &format!(
+            "      <ChapterTimeStart>{}000000</ChapterTimeStart>\n",
+            hms(&chapter.start)
+        );
+        xml += &format!(
+            "      <ChapterTimeEnd>{}000000</ChapterTimeEnd>\n",
+            hms(&chapter_end)
+        );
+        xml += "      <ChapterDisplay>\n";
+        xml += &format!(
+            "        <ChapterString>{}</ChapterString>\n",
+            escape(&chapter.title)
+        );
+        xml += &format!(
+            "        <ChapterLanguage>{}</ChapterLanguage>\n",
+            lang.iso639_2()
+        );
+        xml += "      </ChapterDisplay>\n";
+        xml += "    </ChapterAtom>\n";
+    }
+    xml += "  </EditionEntry>\n</Chapters>\n";
+    xml
+}
+
+#[test]
+fn export_chapters() {
+    let chapters = vec![
+        Chapter {
+            start: TimeDelta::seconds(75),
+            title: "Lap 2".to_string(),
+        },
+        Chapter {
+            start: TimeDelta::milliseconds(3_723_500),
+            title: "Bookmark 1".to_string(),
+        },
+    ];
+    let end = TimeDelta::seconds(4000);
+    assert_eq!(
+        youtube(&chapters),
+        "0:00 Start\n1:15 Lap 2\n1:02:03 Bookmark 1\n"
+    );
+    assert_eq!(
+        ffmetadata(&chapters, end),
+        ";FFMETADATA1\n\n[CHAPTER]\nTIMEBASE=1/1000\nSTART=75000\nEND=3723500\ntitle=Lap 2\n\n[CHAPTER]\nTIMEBASE=1/1000\nSTART=3723500\nEND=4000000\ntitle=Bookmark 1\n"
+    );
+    assert_eq!(
+        webvtt(&chapters, end),
+        "WEBVTT\n\n1\n00:01:15.000 --> 01:02:03.500\nLap 2\n\n2\n01:02:03.500 --> 01:06:40.000\nBookmark 1\n"
+    );
+    let xml = matroska(&chapters, end, Lang::Ja);
+    assert!(xml.contains("<ChapterTimeStart>01:02:03.500000000</ChapterTimeStart>"));
+    assert!(xml.contains("<ChapterLanguage>jpn</ChapterLanguage>"));
+}
@@ -0,0 +1,209 @@
+use std::path::Path;
+
+use crate::error::Fit2SrtError;
+
+// The mean radius of the earth
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+// An example of the CSV with a few well known sites at approximate positions,
+// it is not a dataset of dive sites, a user database is needed for the sites you dive
+const EXAMPLE_SITES: &str = include_str!("../data/example_dive_sites.csv");
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiveSite {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl DiveSite {
+    /// The great circle distance in metres
+    pub fn distance(&self, latitude: f64, longitude: f64) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_long = (longitude - self.longitude).to_radians();
+        let a =
+            (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_long / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// An offline database of dive sites to name the location of a dive
+#[derive(Clone, Debug)]
+pub struct DiveSites {
+    sites: Vec<DiveSite>,
+    // metres
+    tolerance: f64,
+}
+
+impl DiveSites {
+    pub fn new(sites: Vec<DiveSite>) -> Self {
+        Self {
+            sites,
+            tolerance: 1000.0,
+        }
+    }
+
+    /// A few well known sites at approximate positions, an example of the database
+    pub fn example() -> Self {
+        Self::from_csv(EXAMPLE_SITES).expect("example dive sites are valid")
+    }
+
+    /// Open a CSV file, or a GeoJSON file with the `.geojson` or `.json` extension
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send + 'static>> {
+        let content = std::fs::read_to_string(&path)?;
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("geojson" | "json") => Ok(Self::from_geojson(&content)?),
+            _ => Ok(Self::from_csv(&content)?),
+        }
+    }
+
+    /// Parse the CSV of `name,latitude,longitude` in degrees,
+    /// the columns can be in any order if there is a header
+    pub fn from_csv(content: &str) -> Result<Self, Fit2SrtError> {
+        let mut columns = (0, 1, 2);
+        let mut sites = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = split_csv_line(line);
+            if i == 0
+                && fields
+                    .get(columns.1)
+                    .and_then(|f| f.parse::<f64>().ok())
+                    .is_none()
+            {
+                let position = |names: &[&str]| {
+                    fields
+                        .iter()
+                        .position(|f| names.contains(&f.to_lowercase().as_str()))
+                        .ok_or_else(|| {
+                            Fit2SrtError::DiveSiteError(format!("no {} column", names[0]))
+                        })
+                };
+                columns = (
+                    position(&["name", "site"])?,
+                    position(&["latitude", "lat"])?,
+                    position(&["longitude", "long", "lon", "lng"])?,
+                );
+                continue;
+            }
+            let field = |idx: usize| {
+                fields.get(idx).ok_or_else(|| {
+                    Fit2SrtError::DiveSiteError(format!("missing column at line {}", i + 1))
+                })
+            };
+            let degree = |idx: usize| {
+                field(idx)?.parse::<f64>().map_err(|_| {
+                    Fit2SrtError::DiveSiteError(format!("invalid position at line {}", i + 1))
+                })
+            };
+            sites.push(DiveSite {
+                name: field(columns.0)?.to_string(),
+                latitude: degree(columns.1)?,
+                longitude: degree(columns.2)?,
+            });
+        }
+        Ok(Self::new(sites))
+    }
+
+    /// Parse the `Point` features of a GeoJSON with the `name` property
+    pub fn from_geojson(content: &str) -> Result<Self, Fit2SrtError> {
+        let json: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| Fit2SrtError::DiveSiteError(e.to_string()))?;
+        let features = match json.get("features") {
+            Some(serde_json::Value::Array(features)) => features.clone(),
+            _ => vec![json],
+        };
+        let mut sites = Vec::new();
+        for feature in features {
+            let geometry = &feature["geometry"];
+            if geometry["type"] != "Point" {
+                continue;
+            }
+            let (Some(longitude), Some(latitude)) = (
+                geometry["coordinates"][0].as_f64(),
+                geometry["coordinates"][1].as_f64(),
+            ) else {
+                return Err(Fit2SrtError::DiveSiteError(
+                    "invalid point coordinates".to_string(),
+                ));
+            };
+            if let Some(name) = feature["properties"]["name"].as_str() {
+                sites.push(DiveSite {
+                    name: name.to_string(),
+                    latitude,
+                    longitude,
+                });
+            }
+        }
+        Ok(Self::new(sites))
+    }
+
+    /// The distance in metres to match a site
+    pub fn tolerance(&mut self, metres: f64) {
+        self.tolerance = metres;
+    }
+
+    /// The nearest site within the tolerance
+    pub fn nearest(&self, latitude: f64, longitude: f64) -> Option<&DiveSite> {
+        self.sites
+            .iter()
+            .map(|site| (site, site.distance(latitude, longitude)))
+            .filter(|(_, distance)| *distance <= self.tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(site, _)| site)
+    }
+}
+
+/// Split a line of CSV, the fields can be quoted with `"`
+pub(crate) fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+#[test]
+fn match_dive_site() {
+    let mut sites = DiveSites::from_csv(
+        "lat,long,name\n21.9397,120.7449,\"Houbihu, Kenting\"\n21.9491,120.7097,Outer Kenting",
+    )
+    .unwrap();
+    assert_eq!(
+        sites.nearest(21.939216, 120.745365).unwrap().name,
+        "Houbihu, Kenting"
+    );
+    sites.tolerance(10.0);
+    assert!(sites.nearest(21.939216, 120.745365).is_none());
+
+    let sites = DiveSites::from_geojson(
+        r#"{"type": "FeatureCollection", "features": [{"type": "Feature",
+        "geometry": {"type": "Point", "coordinates": [120.7449, 21.9397]},
+        "properties": {"name": "Houbihu"}}]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        sites.nearest(21.939216, 120.745365).unwrap().name,
+        "Houbihu"
+    );
+    assert!(DiveSites::example()
+        .nearest(21.939216, 120.745365)
+        .is_some());
+}
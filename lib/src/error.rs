@@ -6,4 +6,28 @@ pub enum Fit2SrtError {
     MergeError(String),
     #[error("Parse Summary Error: {0}")]
     SummaryError(String),
+    #[error("Invalid frame rate: {0}")]
+    FrameRateError(String),
+    #[error("Unsupported interpolation: {0}")]
+    InterpolationError(String),
+    #[error("Unsupported filter: {0}")]
+    FilterError(String),
+    #[error("Invalid gap: {0}")]
+    GapError(String),
+    #[error("Invalid clip: {0}")]
+    ClipError(String),
+    #[error("Invalid start time: {0}")]
+    StartTimeError(String),
+    #[error("Invalid dive site database: {0}")]
+    DiveSiteError(String),
+    #[error("Unsupported metric: {0}")]
+    MetricError(String),
+    #[error("Unsupported event: {0}")]
+    EventError(String),
+    #[error("Invalid subtitle: {0}")]
+    SubtitleError(String),
+    #[error("Unsupported language: {0}")]
+    LangError(String),
+    #[error("Invalid device quirks: {0}")]
+    QuirkError(String),
 }
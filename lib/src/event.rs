@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+use crate::error::Fit2SrtError;
+
+/// A kind of `event` messages shown as a short cue over the values
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    DepthAlarm,
+    TimeAlarm,
+    SafetyStop,
+    AscentRate,
+    GasSwitch,
+    Bookmark,
+    BatteryLow,
+    // The PPO2 alarms of the loop and the diluent
+    Ppo2Alarm,
+    SetpointSwitch,
+    // Switched to the open circuit or back to the loop
+    Bailout,
+}
+
+impl Event {
+    pub const ALL: [Event; 10] = [
+        Self::DepthAlarm,
+        Self::TimeAlarm,
+        Self::SafetyStop,
+        Self::AscentRate,
+        Self::GasSwitch,
+        Self::Bookmark,
+        Self::BatteryLow,
+        Self::Ppo2Alarm,
+        Self::SetpointSwitch,
+        Self::Bailout,
+    ];
+
+    /// The kind and the text of an `event` message from its `event` field,
+    /// and the `dive_alert` field of `dive_alert` events
+    pub fn from_message(event: &str, dive_alert: Option<&str>) -> Option<(Self, String)> {
+        let (kind, text) = match (event, dive_alert) {
+            ("dive_alert", Some("depth_alert")) => (Self::DepthAlarm, "⚠ Depth alarm"),
+            ("dive_alert", Some("time_alert")) => (Self::TimeAlarm, "⚠ Time alarm"),
+            ("dive_alert", Some("safety_stop_started")) => (Self::SafetyStop, "Safety stop"),
+            ("dive_alert", Some("safety_stop_complete")) => {
+                (Self::SafetyStop, "Safety stop complete")
+            }
+            ("dive_alert", Some("safety_stop_broken")) => {
+                (Self::SafetyStop, "⚠ Safety stop broken")
+            }
+            ("dive_alert", Some("ascent_critical")) => (Self::AscentRate, "⚠ Ascent rate"),
+            ("dive_alert", Some("battery_low" | "battery_critical")) | ("battery_low", _) => {
+                (Self::BatteryLow, "⚠ Battery low")
+            }
+            ("dive_alert", Some("po2_warn")) => (Self::Ppo2Alarm, "⚠ PPO2 warning"),
+            ("dive_alert", Some("po2_crit_high")) => (Self::Ppo2Alarm, "⚠ PPO2 high"),
+            ("dive_alert", Some("po2_crit_low")) => (Self::Ppo2Alarm, "⚠ PPO2 low"),
+            ("dive_alert", Some("po2_ccr_dil_low")) => (Self::Ppo2Alarm, "⚠ Diluent PPO2 low"),
+            ("dive_alert", Some("setpoint_switch_auto_low" | "setpoint_switch_manual_low")) => {
+                (Self::SetpointSwitch, "Setpoint low")
+            }
+            ("dive_alert", Some("setpoint_switch_auto_high" | "setpoint_switch_manual_high")) => {
+                (Self::SetpointSwitch, "Setpoint high")
+            }
+            ("dive_alert", Some("switched_to_open_circuit")) => {
+                (Self::Bailout, "⚠ Bailout to open circuit")
+            }
+            ("dive_alert", Some("switched_to_closed_circuit")) => {
+                (Self::Bailout, "Back on the loop")
+            }
+            ("dive_gas_switched", _) => (Self::GasSwitch, "Gas switch"),
+            ("user_marker", _) => (Self::Bookmark, "Bookmark"),
+            _ => return None,
+        };
+        Some((kind, text.to_string()))
+    }
+}
+
+impl FromStr for Event {
+    type Err = Fit2SrtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "depth_alarm" | "depth" => Ok(Self::DepthAlarm),
+            "time_alarm" | "time" => Ok(Self::TimeAlarm),
+            "safety_stop" => Ok(Self::SafetyStop),
+            "ascent_rate" | "ascent" => Ok(Self::AscentRate),
+            "gas_switch" | "gas" => Ok(Self::GasSwitch),
+            "bookmark" | "marker" => Ok(Self::Bookmark),
+            "battery_low" | "battery" => Ok(Self::BatteryLow),
+            "ppo2_alarm" | "ppo2" => Ok(Self::Ppo2Alarm),
+            "setpoint_switch" | "setpoint" => Ok(Self::SetpointSwitch),
+            "bailout" => Ok(Self::Bailout),
+            _ => Err(Fit2SrtError::EventError(s.to_string())),
+        }
+    }
+}
+
+#[test]
+fn event_cues() {
+    assert_eq!(
+        Event::from_message("dive_alert", Some("ascent_critical")),
+        Some((Event::AscentRate, "⚠ Ascent rate".to_string()))
+    );
+    assert_eq!(
+        Event::from_message("dive_alert", Some("safety_stop_started")),
+        Some((Event::SafetyStop, "Safety stop".to_string()))
+    );
+    // the alert is dismissed
+    assert_eq!(
+        Event::from_message("dive_alert", Some("alert_dismissed_by_timeout")),
+        None
+    );
+    assert_eq!(
+        Event::from_message("dive_alert", Some("setpoint_switch_manual_high")),
+        Some((Event::SetpointSwitch, "Setpoint high".to_string()))
+    );
+    assert_eq!("gas-switch".parse::<Event>().unwrap(), Event::GasSwitch);
+    assert!("unknown".parse::<Event>().is_err());
+}
@@ -0,0 +1,136 @@
+use chrono::TimeDelta;
+use std::str::FromStr;
+
+use crate::error::Fit2SrtError;
+
+/// A filter of the samples of a metric before the cues are generated,
+/// to keep the noise of the sensors and the splashes at the surface out of the cues
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Filter {
+    /// The mean of the samples in the window centred on each sample
+    MovingAverage(TimeDelta),
+    /// The median of the samples in the window centred on each sample,
+    /// which removes a spike shorter than half of the window
+    Median(TimeDelta),
+    /// Drop a sample changing faster than the rate (per second) from the last kept sample
+    MaxRate(f64),
+}
+
+impl Filter {
+    pub fn apply(&self, samples: &[(TimeDelta, f64)]) -> Vec<(TimeDelta, f64)> {
+        match self {
+            Self::MovingAverage(window) => windowed(samples, *window, |values| {
+                values.iter().sum::<f64>() / values.len() as f64
+            }),
+            Self::Median(window) => windowed(samples, *window, |values| {
+                values.sort_by(f64::total_cmp);
+                let mid = values.len() / 2;
+                if values.len() % 2 == 0 {
+                    (values[mid - 1] + values[mid]) / 2.0
+                } else {
+                    values[mid]
+                }
+            }),
+            Self::MaxRate(rate) => {
+                let mut kept: Vec<(TimeDelta, f64)> = Vec::with_capacity(samples.len());
+                for (time, value) in samples {
+                    // the change allowed grows with the time, so a real step is taken later
+                    let outlier = kept.last().is_some_and(|(t, v)| {
+                        let secs = (*time - *t).num_milliseconds() as f64 / 1000.0;
+                        (value - v).abs() > rate * secs
+                    });
+                    if !outlier {
+                        kept.push((*time, *value));
+                    }
+                }
+                kept
+            }
+        }
+    }
+}
+
+/// Apply the filters in order
+pub fn apply_all(filters: &[Filter], samples: Vec<(TimeDelta, f64)>) -> Vec<(TimeDelta, f64)> {
+    filters
+        .iter()
+        .fold(samples, |samples, filter| filter.apply(&samples))
+}
+
+/// The value of each sample from the samples in the window centred on it
+fn windowed(
+    samples: &[(TimeDelta, f64)],
+    window: TimeDelta,
+    reduce: impl Fn(&mut Vec<f64>) -> f64,
+) -> Vec<(TimeDelta, f64)> {
+    let half = window / 2;
+    let (mut lower, mut upper) = (0, 0);
+    let mut values = Vec::new();
+    samples
+        .iter()
+        .map(|(time, _)| {
+            while upper < samples.len() && samples[upper].0 <= *time + half {
+                upper += 1;
+            }
+            while samples[lower].0 < *time - half {
+                lower += 1;
+            }
+            values.clear();
+            values.extend(samples[lower..upper].iter().map(|(_, v)| *v));
+            (*time, reduce(&mut values))
+        })
+        .collect()
+}
+
+impl FromStr for Filter {
+    type Err = Fit2SrtError;
+
+    /// `average:SECONDS`, `median:SECONDS` or `max_rate:PER_SECOND`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || Fit2SrtError::FilterError(s.to_string());
+        let (name, value) = s.split_once(':').ok_or_else(err)?;
+        let value = value.trim().parse::<f64>().map_err(|_| err())?;
+        if !value.is_finite() || value <= 0.0 {
+            return Err(err());
+        }
+        let window = TimeDelta::milliseconds((value * 1000.0) as i64);
+        match name.trim().to_lowercase().replace('-', "_").as_str() {
+            "average" | "moving_average" | "mean" => Ok(Self::MovingAverage(window)),
+            "median" => Ok(Self::Median(window)),
+            "max_rate" | "rate" => Ok(Self::MaxRate(value)),
+            _ => Err(err()),
+        }
+    }
+}
+
+#[test]
+fn filter_samples() {
+    let samples: Vec<(TimeDelta, f64)> = [5.0, 5.2, 9.0, 5.1, 5.3, 5.2]
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| (TimeDelta::seconds(i as i64), v))
+        .collect();
+    let values = |samples: Vec<(TimeDelta, f64)>| -> Vec<f64> {
+        samples.into_iter().map(|(_, v)| v).collect()
+    };
+    // the spike is gone
+    assert_eq!(
+        values(Filter::Median(TimeDelta::seconds(2)).apply(&samples)),
+        vec![5.1, 5.2, 5.2, 5.3, 5.2, 5.25]
+    );
+    let kept = Filter::MaxRate(1.0).apply(&samples);
+    assert_eq!(kept.len(), 5);
+    assert!(kept.iter().all(|(_, v)| *v < 6.0));
+    let averaged = values(Filter::MovingAverage(TimeDelta::seconds(2)).apply(&kept));
+    assert!((averaged[0] - 5.1).abs() < 1e-9);
+    assert_eq!(
+        "median:3".parse::<Filter>().unwrap(),
+        Filter::Median(TimeDelta::seconds(3))
+    );
+    assert_eq!(
+        "max-rate:0.5".parse::<Filter>().unwrap(),
+        Filter::MaxRate(0.5)
+    );
+    assert!("median:-1".parse::<Filter>().is_err());
+    assert!("kalman:1".parse::<Filter>().is_err());
+    assert_eq!(apply_all(&[], samples.clone()).len(), samples.len());
+}
@@ -0,0 +1,100 @@
+use chrono::TimeDelta;
+
+/// The difference of the headings in degrees, from -180 to 180
+pub fn difference(a: f64, b: f64) -> f64 {
+    (a - b + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// The nearest of N, E, S and W
+pub fn cardinal(degrees: f64) -> &'static str {
+    ["N", "E", "S", "W"][((degrees.rem_euclid(360.0) + 45.0) / 90.0) as usize % 4]
+}
+
+/// The circular mean of the headings in the window centred on each sample,
+/// the result is unwrapped to be continuous over north, ex: 359, 361, to be interpolated
+pub fn smooth(samples: &[(TimeDelta, f64)], window: TimeDelta) -> Vec<(TimeDelta, f64)> {
+    let half = window / 2;
+    let (mut lower, mut upper) = (0, 0);
+    let (mut sin, mut cos) = (0f64, 0f64);
+    let mut previous: Option<f64> = None;
+    let mut smoothed = Vec::with_capacity(samples.len());
+    for (time, heading) in samples {
+        while upper < samples.len() && samples[upper].0 <= *time + half {
+            let a = samples[upper].1.to_radians();
+            (sin, cos) = (sin + a.sin(), cos + a.cos());
+            upper += 1;
+        }
+        while lower < samples.len() && samples[lower].0 < *time - half {
+            let a = samples[lower].1.to_radians();
+            (sin, cos) = (sin - a.sin(), cos - a.cos());
+            lower += 1;
+        }
+        let mean = if sin.abs() < 1e-9 && cos.abs() < 1e-9 {
+            *heading
+        } else {
+            sin.atan2(cos).to_degrees()
+        };
+        let value = match previous {
+            Some(p) => p + difference(mean, p),
+            None => mean.rem_euclid(360.0),
+        };
+        smoothed.push((*time, value));
+        previous = Some(value);
+    }
+    smoothed
+}
+
+/// The times the heading has turned at least the angle within the duration,
+/// a turn is not counted again until the duration passed
+pub fn turns(samples: &[(TimeDelta, f64)], min_angle: f64, within: TimeDelta) -> Vec<TimeDelta> {
+    let mut turns: Vec<TimeDelta> = Vec::new();
+    let mut lower = 0;
+    for (time, heading) in samples {
+        while samples[lower].0 < *time - within {
+            lower += 1;
+        }
+        if turns.last().is_some_and(|last| *time - *last <= within) {
+            continue;
+        }
+        if samples[lower..]
+            .iter()
+            .take_while(|(t, _)| t < time)
+            .any(|(_, h)| difference(*heading, *h).abs() >= min_angle)
+        {
+            turns.push(*time);
+        }
+    }
+    turns
+}
+
+#[test]
+fn smooth_heading() {
+    let samples: Vec<(TimeDelta, f64)> = [350.0, 10.0, 350.0, 10.0, 30.0, 30.0]
+        .into_iter()
+        .enumerate()
+        .map(|(i, h)| (TimeDelta::seconds(i as i64), h))
+        .collect();
+    let smoothed = smooth(&samples, TimeDelta::seconds(2));
+    // the mean over the north is not 180
+    assert!(smoothed[1].1 > 355.0 && smoothed[1].1 < 365.0);
+    // unwrapped after the north
+    assert!(smoothed[5].1 > 380.0);
+    // a negative window has no sample
+    assert_eq!(
+        smooth(&samples, TimeDelta::seconds(-5)).len(),
+        samples.len()
+    );
+    assert_eq!(cardinal(42.0), "N");
+    assert_eq!(cardinal(359.0 + 1.0), "N");
+    assert_eq!(cardinal(260.0), "W");
+    assert_eq!(crate::Metric::Heading.format(359.6, "°"), "N 0°");
+    assert_eq!(crate::Metric::Heading.format(402.0, "°"), "N 42°");
+
+    let samples: Vec<(TimeDelta, f64)> = (0..60)
+        .map(|i| (TimeDelta::seconds(i), if i < 30 { 40.0 } else { 220.0 }))
+        .collect();
+    assert_eq!(
+        turns(&samples, 150.0, TimeDelta::seconds(30)),
+        vec![TimeDelta::seconds(30)]
+    );
+}
@@ -0,0 +1,134 @@
+use chrono::TimeDelta;
+use std::str::FromStr;
+
+use crate::error::Fit2SrtError;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    Linear,
+    /// Monotone cubic spline (Fritsch-Carlson), which never overshoots the samples,
+    /// so the depth will not go above the surface between two samples
+    Spline,
+}
+
+impl Interpolation {
+    /// Resample the samples on the interval from the first sample to the last sample
+    pub fn resample(
+        &self,
+        samples: &[(TimeDelta, f64)],
+        interval: TimeDelta,
+    ) -> Vec<(TimeDelta, f64)> {
+        // samples on the same time are not able to interpolate, keep the later one
+        let mut points: Vec<(f64, f64)> = Vec::with_capacity(samples.len());
+        for (time, value) in samples {
+            let x = seconds(time);
+            match points.last_mut() {
+                Some(last) if last.0 >= x => last.1 = *value,
+                _ => points.push((x, *value)),
+            }
+        }
+        let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+            return Vec::new();
+        };
+        if points.len() == 1 || interval <= TimeDelta::zero() {
+            return vec![(first.0, points[0].1)];
+        }
+
+        let slopes = match self {
+            Self::Linear => Vec::new(),
+            Self::Spline => monotone_slopes(&points),
+        };
+
+        let mut resampled = Vec::new();
+        let mut segment = 0;
+        let mut time = first.0;
+        while time <= last.0 {
+            let x = seconds(&time);
+            while segment + 2 < points.len() && points[segment + 1].0 <= x {
+                segment += 1;
+            }
+            let (x0, y0) = points[segment];
+            let (x1, y1) = points[segment + 1];
+            let h = x1 - x0;
+            let t = ((x - x0) / h).clamp(0.0, 1.0);
+            let value = match self {
+                Self::Linear => y0 + (y1 - y0) * t,
+                Self::Spline => {
+                    let (t2, t3) = (t * t, t * t * t);
+                    (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+                        + (t3 - 2.0 * t2 + t) * h * slopes[segment]
+                        + (-2.0 * t3 + 3.0 * t2) * y1
+                        + (t3 - t2) * h * slopes[segment + 1]
+                }
+            };
+            resampled.push((time, value));
+            time += interval;
+        }
+        resampled
+    }
+}
+
+fn seconds(time: &TimeDelta) -> f64 {
+    time.num_microseconds().unwrap_or(i64::MAX) as f64 / 1_000_000.0
+}
+
+/// The tangents of the spline on each point, flatten on the local extremes to keep monotone
+fn monotone_slopes(points: &[(f64, f64)]) -> Vec<f64> {
+    let secants: Vec<f64> = points
+        .windows(2)
+        .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0))
+        .collect();
+    let mut slopes = Vec::with_capacity(points.len());
+    slopes.push(secants[0]);
+    for i in 1..points.len() - 1 {
+        let (d0, d1) = (secants[i - 1], secants[i]);
+        if d0 * d1 <= 0.0 {
+            slopes.push(0.0);
+        } else {
+            let h0 = points[i].0 - points[i - 1].0;
+            let h1 = points[i + 1].0 - points[i].0;
+            let (w0, w1) = (2.0 * h1 + h0, h1 + 2.0 * h0);
+            slopes.push((w0 + w1) / (w0 / d0 + w1 / d1));
+        }
+    }
+    slopes.push(secants[secants.len() - 1]);
+    slopes
+}
+
+impl FromStr for Interpolation {
+    type Err = Fit2SrtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "linear" => Ok(Self::Linear),
+            "spline" => Ok(Self::Spline),
+            _ => Err(Fit2SrtError::InterpolationError(s.to_string())),
+        }
+    }
+}
+
+#[test]
+fn linear_resample() {
+    let samples = [
+        (TimeDelta::zero(), 1.0),
+        (TimeDelta::seconds(2), 2.0),
+        (TimeDelta::seconds(3), 0.0),
+    ];
+    let resampled = Interpolation::Linear.resample(&samples, TimeDelta::milliseconds(500));
+    let values: Vec<f64> = resampled.iter().map(|(_, v)| *v).collect();
+    assert_eq!(values, vec![1.0, 1.25, 1.5, 1.75, 2.0, 1.0, 0.0]);
+    assert_eq!(resampled[6].0, TimeDelta::seconds(3));
+}
+
+#[test]
+fn spline_not_overshoot() {
+    let samples = [
+        (TimeDelta::zero(), 0.0),
+        (TimeDelta::seconds(1), 3.0),
+        (TimeDelta::seconds(2), 3.0),
+        (TimeDelta::seconds(5), 0.0),
+    ];
+    for (_, v) in Interpolation::Spline.resample(&samples, TimeDelta::milliseconds(250)) {
+        assert!((0.0..=3.0).contains(&v));
+    }
+}
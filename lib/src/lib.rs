@@ -1,6 +1,39 @@
+pub mod ccr;
+pub mod chapter;
+pub mod dive_site;
 mod error;
+pub mod event;
+pub mod filter;
+pub mod heading;
+pub mod interpolation;
+pub mod locale;
+pub mod metric;
+pub mod overlay;
+pub mod quirks;
+pub mod render;
+pub mod safety_stop;
 pub mod srt_iter;
+pub mod subtitle;
 pub mod summary;
+pub mod thermocline;
+pub mod timecode;
+pub mod waypoint;
 
-pub use srt_iter::SrtGenerator;
-pub use summary::Summary;
+pub use chapter::Chapter;
+pub use dive_site::{DiveSite, DiveSites};
+pub use error::Fit2SrtError;
+pub use event::Event;
+pub use filter::Filter;
+pub use interpolation::Interpolation;
+pub use locale::{Lang, Locale};
+pub use metric::Metric;
+pub use overlay::Overlay;
+pub use quirks::{DeviceQuirks, FieldQuirk, Quirks};
+pub use render::{Frame, ProfileChart};
+pub use safety_stop::SafetyStop;
+pub use srt_iter::{Clip, Cue, Gap, Placement, SrtGenerator, VideoMap};
+pub use subtitle::Overlap;
+pub use summary::{Summary, SummaryCue, SummaryPosition};
+pub use thermocline::Thermocline;
+pub use timecode::{FrameRate, TimeFormat};
+pub use waypoint::Waypoint;
@@ -0,0 +1,273 @@
+use std::str::FromStr;
+
+use crate::error::Fit2SrtError;
+
+/// The languages of the generated labels
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    /// Traditional Chinese
+    Zh,
+    Ja,
+}
+
+impl Lang {
+    pub const ALL: [Self; 3] = [Self::En, Self::Zh, Self::Ja];
+
+    /// The language tag, ex: the suffix of `video.zh.srt`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Zh => "zh",
+            Self::Ja => "ja",
+        }
+    }
+
+    /// The ISO 639-2 code, ex: the language of Matroska chapters
+    pub fn iso639_2(&self) -> &'static str {
+        match self {
+            Self::En => "eng",
+            Self::Zh => "chi",
+            Self::Ja => "jpn",
+        }
+    }
+
+    /// The name of the language in itself
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::En => "English",
+            Self::Zh => "中文",
+            Self::Ja => "日本語",
+        }
+    }
+}
+
+impl std::fmt::Display for Lang {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Lang {
+    type Err = Fit2SrtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lang = s.to_lowercase().replace('_', "-");
+        match lang.split('-').next().unwrap_or_default() {
+            "en" | "english" => Ok(Self::En),
+            "zh" | "chinese" => Ok(Self::Zh),
+            "ja" | "jp" | "japanese" => Ok(Self::Ja),
+            _ => Err(Fit2SrtError::LangError(s.to_string())),
+        }
+    }
+}
+
+// The labels and the texts of the GUI in English, Chinese and Japanese
+const TRANSLATIONS: &[(&str, &str, &str)] = &[
+    ("⚠ Depth alarm", "⚠ 深度警報", "⚠ 深度アラーム"),
+    ("⚠ Time alarm", "⚠ 時間警報", "⚠ 時間アラーム"),
+    ("Safety stop complete", "安全停留完成", "安全停止完了"),
+    ("⚠ Safety stop broken", "⚠ 安全停留中斷", "⚠ 安全停止違反"),
+    ("⚠ Ascent rate", "⚠ 上升速度過快", "⚠ 浮上速度超過"),
+    ("⚠ Battery low", "⚠ 電量不足", "⚠ バッテリー低下"),
+    ("⚠ PPO2 warning", "⚠ PPO2 警告", "⚠ PPO2 警告"),
+    ("⚠ PPO2 high", "⚠ PPO2 過高", "⚠ PPO2 高"),
+    ("⚠ PPO2 low", "⚠ PPO2 過低", "⚠ PPO2 低"),
+    (
+        "⚠ Diluent PPO2 low",
+        "⚠ 稀釋氣 PPO2 過低",
+        "⚠ 希釈ガス PPO2 低",
+    ),
+    ("Setpoint low", "低設定點", "低セットポイント"),
+    ("Setpoint high", "高設定點", "高セットポイント"),
+    (
+        "⚠ Bailout to open circuit",
+        "⚠ 緊急切換開放式",
+        "⚠ オープンサーキットへベイルアウト",
+    ),
+    ("Back on the loop", "回到迴路", "ループに復帰"),
+    ("Gas switch", "切換氣體", "ガス切替"),
+    ("Bookmark", "書籤", "ブックマーク"),
+    ("Lap", "圈", "ラップ"),
+    ("Safety stop", "安全停留", "安全停止"),
+    ("Thermocline", "斜溫層", "水温躍層"),
+    ("Turn around", "折返", "折り返し"),
+    ("Dive time", "潛水時間", "潜水時間"),
+    ("Elapsed", "經過", "経過"),
+    ("SP", "設定點", "SP"),
+    ("OC", "開放式", "OC"),
+    ("Air", "空氣", "空気"),
+    ("N", "北", "北"),
+    ("E", "東", "東"),
+    ("S", "南", "南"),
+    ("W", "西", "西"),
+    // the texts of the screens of the GUI
+    ("Introduction", "介紹", "はじめに"),
+    ("Setup inputs", "設定輸入", "入力の設定"),
+    ("End", "完成", "完了"),
+    ("CryptoDonate", "加密貨幣贊助", "暗号資産で寄付"),
+    ("Back", "上一步", "戻る"),
+    ("Next", "下一步", "次へ"),
+    ("Open", "開啟", "開く"),
+    ("Welcome!", "歡迎！", "ようこそ！"),
+    (
+        "This is a simple tool for you to make your diving log as video subtitles.",
+        "這是一個把潛水日誌做成影片字幕的簡單工具。",
+        "ダイブログを動画の字幕にするシンプルなツールです。",
+    ),
+    (
+        "You can see some sample video here:",
+        "範例影片在這裡：",
+        "サンプル動画はこちら：",
+    ),
+    (
+        "If you want to any scuba diving crouse, please contact with me.",
+        "如果想參加潛水課程，歡迎與我聯絡。",
+        "ダイビングコースに興味があれば、ご連絡ください。",
+    ),
+    (
+        "1. Fit file loaded:",
+        "1. 已載入 FIT 檔：",
+        "1. 読み込んだ FIT ファイル：",
+    ),
+    (
+        "1. Select the fit file from you diving computer.",
+        "1. 選擇潛水電腦的 FIT 檔。",
+        "1. ダイブコンピューターの FIT ファイルを選んでください。",
+    ),
+    (
+        "2. Setup the starting time of the video",
+        "2. 設定影片的開始時間",
+        "2. 動画の開始時刻を設定",
+    ),
+    (
+        "3. Dive sites loaded:",
+        "3. 已載入潛點：",
+        "3. 読み込んだダイブサイト：",
+    ),
+    (
+        "3. Name the location with your dive sites, a CSV or a GeoJSON (optional)",
+        "3. 用你的潛點 CSV 或 GeoJSON 命名地點（選填）",
+        "3. ダイブサイトの CSV か GeoJSON で場所に名前を付ける（任意）",
+    ),
+    ("All Done!", "完成！", "完了！"),
+    (
+        "The .srt file is created:",
+        "已建立 .srt 檔：",
+        ".srt ファイルを作成しました：",
+    ),
+    (
+        "You can upload .srt to youtube or use it in video editor.",
+        "可以把 .srt 上傳到 YouTube，或在影片編輯器中使用。",
+        ".srt は YouTube にアップロードしたり、動画編集ソフトで使えます。",
+    ),
+    (
+        "If you like this project, please buy me a coffee via paypal or bitcoin to support me.",
+        "如果喜歡這個專案，歡迎透過 PayPal 或比特幣請我喝杯咖啡。",
+        "このプロジェクトを気に入ったら、PayPal かビットコインでコーヒーをおごってください。",
+    ),
+    ("Help us", "幫助我們", "ご支援ください"),
+    (
+        "If you want to donate with crypto.",
+        "如果想用加密貨幣贊助。",
+        "暗号資産で寄付する場合。",
+    ),
+    (
+        "Please help us with Bitcoin.",
+        "請用比特幣幫助我們。",
+        "ビットコインでご支援ください。",
+    ),
+];
+
+/// The language, the number format and the date format of the generated texts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Locale {
+    pub lang: Lang,
+    // Write the decimals with a comma, ex: 4,2m
+    pub decimal_comma: bool,
+    // The `strftime` format of the date in the summary
+    pub date_format: String,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new(Lang::default())
+    }
+}
+
+impl Locale {
+    /// The formats of the language, the decimals are with a point
+    pub fn new(lang: Lang) -> Self {
+        Self {
+            lang,
+            decimal_comma: false,
+            date_format: match lang {
+                Lang::En => "%Y-%m-%d",
+                Lang::Zh | Lang::Ja => "%Y年%-m月%-d日",
+            }
+            .to_string(),
+        }
+    }
+
+    /// The label in the language, or the label itself if it has no translation
+    pub fn text<'a>(&self, label: &'a str) -> &'a str {
+        let column = match self.lang {
+            Lang::En => return label,
+            Lang::Zh => |t: &(&'static str, &'static str, &'static str)| t.1,
+            Lang::Ja => |t: &(&'static str, &'static str, &'static str)| t.2,
+        };
+        TRANSLATIONS
+            .iter()
+            .find(|t| t.0 == label)
+            .map_or(label, column)
+    }
+
+    /// The decimal points between digits are written as the decimal separator of the locale
+    pub fn number(&self, text: &str) -> String {
+        if !self.decimal_comma {
+            return text.to_string();
+        }
+        let chars: Vec<char> = text.chars().collect();
+        chars
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let between_digits = i > 0
+                    && chars[i - 1].is_ascii_digit()
+                    && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit());
+                if *c == '.' && between_digits {
+                    ','
+                } else {
+                    *c
+                }
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn localise_labels() {
+    assert_eq!("zh-TW".parse::<Lang>().unwrap(), Lang::Zh);
+    assert_eq!("JA".parse::<Lang>().unwrap(), Lang::Ja);
+    assert!("fr".parse::<Lang>().is_err());
+    let zh = Locale::new(Lang::Zh);
+    assert_eq!(zh.text("Turn around"), "折返");
+    assert_eq!(zh.text("Houbihu"), "Houbihu");
+    assert_eq!(
+        Locale::new(Lang::Ja).text("⚠ Depth alarm"),
+        "⚠ 深度アラーム"
+    );
+    assert_eq!(Locale::default().text("Lap"), "Lap");
+    // the texts of the GUI
+    assert_eq!(Locale::new(Lang::Ja).text("Next"), "次へ");
+    assert_eq!(zh.number("4.2m"), "4.2m");
+    let comma = Locale {
+        decimal_comma: true,
+        ..Locale::default()
+    };
+    assert_eq!(
+        comma.number("4.2m (max: 12.25m). 1.3"),
+        "4,2m (max: 12,25m). 1,3"
+    );
+}
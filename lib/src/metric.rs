@@ -0,0 +1,296 @@
+use chrono::{NaiveTime, TimeDelta};
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::error::Fit2SrtError;
+use crate::heading;
+use crate::interpolation::Interpolation;
+use crate::locale::Locale;
+use crate::summary::duration_format;
+
+/// A value of `record` messages shown in the cues,
+/// or a time of the records in seconds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Depth,
+    HeartRate,
+    Temperature,
+    Heading,
+    // Since the descent
+    DiveTime,
+    // Since the start of the FIT file
+    ElapsedTime,
+    // The local time of day
+    Clock,
+    // The partial pressure of oxygen in bar
+    Ppo2,
+    // The setpoint of a rebreather in bar from the setpoint switches, 0 on the open circuit
+    Setpoint,
+    // A developer field named by `field_description` messages,
+    // of any developer if the developer data index is not given
+    Developer { index: Option<u8>, name: String },
+}
+
+impl Metric {
+    /// The field of `record` messages, none for the times computed from the timestamps
+    /// and the setpoint computed from the changes of the loop
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            Self::Depth => Some("depth"),
+            Self::HeartRate => Some("heart_rate"),
+            Self::Temperature => Some("temperature"),
+            Self::Heading => Some("heading"),
+            Self::DiveTime | Self::ElapsedTime | Self::Clock | Self::Setpoint => None,
+            Self::Ppo2 => Some("po2"),
+            Self::Developer { name, .. } => Some(name),
+        }
+    }
+
+    /// The unit if the field has none
+    pub fn default_unit(&self) -> &'static str {
+        match self {
+            Self::Depth => "m",
+            Self::HeartRate => "bpm",
+            Self::Temperature => "C",
+            Self::Heading => "°",
+            Self::DiveTime | Self::ElapsedTime => "",
+            Self::Clock => "%H:%M",
+            Self::Ppo2 | Self::Setpoint => "bar",
+            Self::Developer { .. } => "",
+        }
+    }
+
+    /// The precision of the value
+    pub fn tick(&self) -> f64 {
+        match self {
+            Self::Depth => 0.1,
+            Self::HeartRate | Self::Temperature | Self::Heading => 1.0,
+            Self::DiveTime | Self::ElapsedTime | Self::Clock => 1.0,
+            Self::Ppo2 | Self::Setpoint | Self::Developer { .. } => 0.01,
+        }
+    }
+
+    /// A cue is emitted if the value changes more than it
+    pub fn threshold(&self) -> f64 {
+        match self {
+            Self::Depth => 0.1,
+            Self::HeartRate => 1.0,
+            Self::Temperature => 0.5,
+            Self::Heading => 5.0,
+            Self::Ppo2 => 0.05,
+            Self::Setpoint => 0.0,
+            Self::DiveTime | Self::ElapsedTime | Self::Clock | Self::Developer { .. } => 0.0,
+        }
+    }
+
+    /// The metric is the developer field of the developer data index
+    pub fn is_developer_field(&self, index: u8, field: &str) -> bool {
+        match self {
+            Self::Developer { index: i, name } => {
+                i.is_none_or(|i| i == index) && name.eq_ignore_ascii_case(field)
+            }
+            _ => false,
+        }
+    }
+
+    /// The heading is always in degrees with the cardinal direction, ex: N 42°,
+    /// the PPO2 and the setpoint are in bar, though the profile of `po2` says percent,
+    /// and the unit of the clock is the format of `strftime`, ex: %H:%M
+    pub fn format(&self, value: f64, unit: &str) -> String {
+        self.format_in(value, unit, &Locale::default())
+    }
+
+    /// The value with the labels and the decimal separator of the locale
+    pub fn format_in(&self, value: f64, unit: &str, locale: &Locale) -> String {
+        match self {
+            Self::Depth => locale.number(&format!("{value:.1}{unit}")),
+            Self::HeartRate | Self::Temperature => format!("{value:.0}{unit}"),
+            Self::Heading => {
+                let degrees = value.round().rem_euclid(360.0);
+                format!("{} {degrees:.0}°", locale.text(heading::cardinal(degrees)))
+            }
+            Self::DiveTime => format!(
+                "{} {}",
+                locale.text("Dive time"),
+                duration_format(value as i64)
+            ),
+            Self::ElapsedTime => format!(
+                "{} {}",
+                locale.text("Elapsed"),
+                duration_format(value as i64)
+            ),
+            Self::Clock => {
+                // an invalid format is an error of `Display` instead of a panic of `to_string`
+                let mut text = String::new();
+                if let Some(t) =
+                    NaiveTime::from_num_seconds_from_midnight_opt((value as u32) % 86400, 0)
+                {
+                    let _ = write!(text, "{}", t.format(unit));
+                }
+                text
+            }
+            Self::Ppo2 => locale.number(&format!("PPO2 {value:.2}")),
+            Self::Setpoint if value > 0.0 => {
+                locale.number(&format!("{} {value:.2}", locale.text("SP")))
+            }
+            Self::Setpoint => locale.text("OC").to_string(),
+            Self::Developer { .. } => {
+                locale.number(&format!("{}{unit}", (value * 100.0).round() / 100.0))
+            }
+        }
+    }
+
+    /// The value shown at the first sample and the changes after it
+    pub fn changes(
+        &self,
+        samples: &[(TimeDelta, f64)],
+        unit: &str,
+        threshold: f64,
+        interpolation: Option<(Interpolation, TimeDelta)>,
+        locale: &Locale,
+    ) -> (String, Vec<(TimeDelta, String)>) {
+        let tick = self.tick();
+        let Some((_, first_value)) = samples.first() else {
+            return (String::new(), Vec::new());
+        };
+        let baseline = self.format_in((first_value / tick).round() * tick, unit, locale);
+        let mut previous_value = *first_value;
+        let resampled = match interpolation {
+            Some((method, interval)) => method.resample(samples, interval),
+            None => samples.to_vec(),
+        };
+        let mut changes: Vec<(TimeDelta, String)> = Vec::new();
+        for (time, value) in resampled.into_iter().skip(1) {
+            let rounded_value = (value / tick).round() * tick;
+            let value_str = self.format_in(rounded_value, unit, locale);
+            let previous_value_str = changes.last().map_or(&baseline, |(_, text)| text);
+            if value_str != *previous_value_str
+                && (rounded_value - previous_value).abs() > threshold
+            {
+                changes.push((time, value_str));
+                previous_value = rounded_value;
+            }
+        }
+        (baseline, changes)
+    }
+}
+
+impl FromStr for Metric {
+    type Err = Fit2SrtError;
+
+    /// Parse the name of a metric, or `dev:NAME` or `dev:INDEX:NAME` of a developer field
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(field) = s.strip_prefix("dev:") {
+            let (index, name) = match field.split_once(':') {
+                Some((index, name)) => (
+                    Some(
+                        index
+                            .parse::<u8>()
+                            .map_err(|_| Fit2SrtError::MetricError(s.to_string()))?,
+                    ),
+                    name,
+                ),
+                None => (None, field),
+            };
+            if name.is_empty() {
+                return Err(Fit2SrtError::MetricError(s.to_string()));
+            }
+            return Ok(Self::Developer {
+                index,
+                name: name.to_string(),
+            });
+        }
+        match s.to_lowercase().as_str() {
+            "depth" => Ok(Self::Depth),
+            "heart_rate" | "heart-rate" | "hr" => Ok(Self::HeartRate),
+            "temperature" | "temp" => Ok(Self::Temperature),
+            "heading" | "compass" => Ok(Self::Heading),
+            "dive_time" | "dive-time" => Ok(Self::DiveTime),
+            "elapsed_time" | "elapsed-time" | "elapsed" => Ok(Self::ElapsedTime),
+            "clock" | "time_of_day" | "time-of-day" => Ok(Self::Clock),
+            "ppo2" | "po2" => Ok(Self::Ppo2),
+            "setpoint" | "sp" => Ok(Self::Setpoint),
+            _ => Err(Fit2SrtError::MetricError(s.to_string())),
+        }
+    }
+}
+
+#[test]
+fn clock_changes() {
+    let samples: Vec<(TimeDelta, f64)> = (0..120)
+        .map(|i| (TimeDelta::seconds(i), (10 * 3600 + 41 * 60 + 30 + i) as f64))
+        .collect();
+    let (baseline, changes) =
+        Metric::Clock.changes(&samples, "%H:%M", 0.0, None, &Locale::default());
+    assert_eq!(baseline, "10:41");
+    assert_eq!(
+        changes,
+        vec![
+            (TimeDelta::seconds(30), "10:42".to_string()),
+            (TimeDelta::seconds(90), "10:43".to_string())
+        ]
+    );
+    assert_eq!(Metric::DiveTime.format(1421.0, ""), "Dive time 23:41");
+    // the times are not fields of the records
+    assert_eq!("clock".parse::<Metric>().unwrap().field(), None);
+    let locale = Locale {
+        decimal_comma: true,
+        ..Locale::new(crate::Lang::Ja)
+    };
+    assert_eq!(
+        Metric::DiveTime.format_in(1421.0, "", &locale),
+        "潜水時間 23:41"
+    );
+    assert_eq!(Metric::Depth.format_in(4.2, "m", &locale), "4,2m");
+    assert_eq!(Metric::Heading.format_in(88.0, "°", &locale), "東 88°");
+}
+
+#[test]
+fn heart_rate_changes() {
+    let samples: Vec<(TimeDelta, f64)> = [81.0, 82.0, 84.0, 85.0, 82.0]
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| (TimeDelta::seconds(i as i64), v))
+        .collect();
+    let (baseline, changes) =
+        Metric::HeartRate.changes(&samples, "bpm", 1.0, None, &Locale::default());
+    assert_eq!(baseline, "81bpm");
+    assert_eq!(
+        changes,
+        vec![
+            (TimeDelta::seconds(2), "84bpm".to_string()),
+            (TimeDelta::seconds(4), "82bpm".to_string())
+        ]
+    );
+    let (_, changes) = Metric::HeartRate.changes(&samples, "bpm", 0.0, None, &Locale::default());
+    assert_eq!(changes.len(), 4);
+    // the threshold applies to the interpolated values too
+    let (_, changes) = Metric::HeartRate.changes(
+        &samples,
+        "bpm",
+        2.0,
+        Some((Interpolation::Linear, TimeDelta::milliseconds(500))),
+        &Locale::default(),
+    );
+    assert_eq!(changes, vec![(TimeDelta::seconds(2), "84bpm".to_string())]);
+}
+
+#[test]
+fn developer_metric() {
+    let metric: Metric = "dev:0:ppo2".parse().unwrap();
+    assert_eq!(
+        metric,
+        Metric::Developer {
+            index: Some(0),
+            name: "ppo2".to_string()
+        }
+    );
+    assert!(metric.is_developer_field(0, "PPO2"));
+    assert!(!metric.is_developer_field(1, "ppo2"));
+    assert!("dev:ppo2"
+        .parse::<Metric>()
+        .unwrap()
+        .is_developer_field(1, "ppo2"));
+    assert!("dev:x:ppo2".parse::<Metric>().is_err());
+    assert_eq!(metric.format(1.2000000000000002, "bar"), "1.2bar");
+}
@@ -0,0 +1,272 @@
+use chrono::{NaiveTime, TimeDelta};
+use std::path::Path;
+
+use crate::render::{frame_times, value_at, Frame, Rgba, TRANSPARENT};
+use crate::srt_iter::SrtIter;
+use crate::timecode::FrameRate;
+
+// The width and the height of a glyph
+const GLYPH_WIDTH: i64 = 5;
+const GLYPH_HEIGHT: i64 = 7;
+
+/// The rows of a 5x7 glyph, the highest bit of 5 is the left pixel
+fn glyph(c: char) -> [u8; 7] {
+    match c {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '°' => [0x0C, 0x12, 0x12, 0x0C, 0x00, 0x00, 0x00],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'f' => [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08],
+        'm' => [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11],
+        't' => [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06],
+        _ => [0; 7],
+    }
+}
+
+/// The values shown on a frame of the overlay
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OverlayValues {
+    pub time: Option<NaiveTime>,
+    pub depth: Option<f64>,
+    // The bottom of the depth gauge
+    pub max_depth: f64,
+    pub depth_unit: &'static str,
+    pub temperature: Option<f64>,
+    pub temperature_unit: &'static str,
+    // degrees clockwise from the north
+    pub heading: Option<f64>,
+}
+
+/// Transparent frames to burn into a video, with a depth gauge on the right,
+/// the time and the temperature on the top left and a compass on the bottom left
+#[derive(Clone, Debug)]
+pub struct Overlay {
+    pub width: u32,
+    pub height: u32,
+    pub colour: Rgba,
+    // The outline of text and gauges to be seen on a bright background
+    pub shadow: Rgba,
+    pub marker: Rgba,
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            colour: [255, 255, 255, 255],
+            shadow: [0, 0, 0, 160],
+            marker: [255, 64, 64, 255],
+        }
+    }
+}
+
+impl Overlay {
+    /// The size of a pixel of the glyphs
+    fn scale(&self) -> i64 {
+        (self.height as i64 / 180).max(1)
+    }
+
+    fn margin(&self) -> i64 {
+        self.scale() * 6
+    }
+
+    fn text_width(&self, text: &str, scale: i64) -> i64 {
+        text.chars().count() as i64 * (GLYPH_WIDTH + 1) * scale - scale
+    }
+
+    /// Draw the text with the top left corner at the position
+    fn text(&self, frame: &mut Frame, x: i64, y: i64, text: &str, scale: i64) {
+        for (colour, offset) in [(self.shadow, scale.max(2) / 2), (self.colour, 0)] {
+            for (i, c) in text.chars().enumerate() {
+                let left = x + i as i64 * (GLYPH_WIDTH + 1) * scale + offset;
+                for (row, bits) in glyph(c).iter().enumerate() {
+                    for col in 0..GLYPH_WIDTH {
+                        if bits & (0x10 >> col) != 0 {
+                            frame.fill_rect(
+                                left + col * scale,
+                                y + row as i64 * scale + offset,
+                                scale,
+                                scale,
+                                colour,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn depth_gauge(&self, frame: &mut Frame, values: &OverlayValues) {
+        let (s, m) = (self.scale(), self.margin());
+        let (width, top, bottom) = (4 * s, m, self.height as i64 - m);
+        let x = self.width as i64 - m - width;
+        let level = |depth: f64| {
+            top + ((depth / values.max_depth).clamp(0.0, 1.0) * (bottom - top) as f64) as i64
+        };
+        frame.fill_rect(x, top, width, bottom - top, self.shadow);
+        if let Some(depth) = values.depth {
+            frame.fill_rect(x, top, width, level(depth) - top, self.colour);
+        }
+        // a tick every 5 units
+        let mut tick = 0.0;
+        while tick <= values.max_depth {
+            frame.fill_rect(x - 2 * s, level(tick) - s / 2, 2 * s, s.max(1), self.colour);
+            tick += 5.0;
+        }
+        if let Some(depth) = values.depth {
+            let text = format!("{depth:.1}{}", values.depth_unit);
+            let y = (level(depth) - GLYPH_HEIGHT * s / 2).clamp(top, bottom - GLYPH_HEIGHT * s);
+            self.text(frame, x - 3 * s - self.text_width(&text, s), y, &text, s);
+        }
+    }
+
+    fn compass(&self, frame: &mut Frame, heading: f64) {
+        let (s, m) = (self.scale(), self.margin());
+        let radius = (14 * s) as f64;
+        let centre = ((m as f64) + radius, self.height as f64 - m as f64 - radius);
+        let point = |degrees: f64, r: f64| {
+            let a = degrees.to_radians();
+            (centre.0 + r * a.sin(), centre.1 - r * a.cos())
+        };
+        let ring: Vec<(f64, f64)> = (0..=72).map(|i| point(i as f64 * 5.0, radius)).collect();
+        frame.line(&ring, (s + 1) as f64, self.shadow);
+        frame.line(&ring, s as f64, self.colour);
+        let letter = (s / 2).max(1);
+        for (c, degrees) in [('N', 0.0), ('E', 90.0), ('S', 180.0), ('W', 270.0)] {
+            let (x, y) = point(degrees, radius - (GLYPH_HEIGHT * letter) as f64);
+            self.text(
+                frame,
+                x as i64 - GLYPH_WIDTH * letter / 2,
+                y as i64 - GLYPH_HEIGHT * letter / 2,
+                &c.to_string(),
+                letter,
+            );
+        }
+        frame.line(
+            &[centre, point(heading, radius * 0.6)],
+            (s * 2) as f64,
+            self.marker,
+        );
+        let text = format!("{:.0}°", heading.rem_euclid(360.0));
+        self.text(
+            frame,
+            centre.0 as i64 + radius as i64 + 2 * s,
+            centre.1 as i64 - GLYPH_HEIGHT * s / 2,
+            &text,
+            s,
+        );
+    }
+
+    pub fn frame(&self, values: &OverlayValues) -> Frame {
+        let mut frame = Frame::new(self.width, self.height, TRANSPARENT);
+        let (s, m) = (self.scale(), self.margin());
+        let mut y = m;
+        if let Some(time) = values.time {
+            self.text(&mut frame, m, y, &time.format("%H:%M:%S").to_string(), s);
+            y += (GLYPH_HEIGHT + 3) * s;
+        }
+        if let Some(temperature) = values.temperature {
+            let text = format!("{temperature:.0}{}", values.temperature_unit);
+            self.text(&mut frame, m, y, &text, s);
+        }
+        self.depth_gauge(&mut frame, values);
+        if let Some(heading) = values.heading {
+            self.compass(&mut frame, heading);
+        }
+        frame
+    }
+
+    /// The bottom of the depth gauge, the max depth of the dive rounded up to 5 units
+    pub fn gauge_depth(iter: &SrtIter) -> f64 {
+        let max_depth = iter.profile().iter().map(|(_, d)| *d).fold(0f64, f64::max);
+        ((max_depth / 5.0).ceil() * 5.0).max(5.0)
+    }
+
+    /// The values of the dive at the time from the beginning of the video,
+    /// with the bottom of the depth gauge of `gauge_depth`
+    pub fn values(&self, iter: &SrtIter, t: TimeDelta, max_depth: f64) -> OverlayValues {
+        let profile = iter.profile();
+        // the temperature changes slowly, the last sample is shown
+        let temperatures = iter.temperatures();
+        let temperature = temperatures
+            .partition_point(|(time, _)| *time <= t)
+            .checked_sub(1)
+            .or((!temperatures.is_empty()).then_some(0))
+            .map(|idx| temperatures[idx].1);
+        OverlayValues {
+            time: iter.reference().map(|r| (r + t).time()),
+            depth: value_at(profile, t),
+            max_depth,
+            depth_unit: iter.summary.depth_unit(),
+            temperature,
+            temperature_unit: iter.summary.temp_unit(),
+            heading: value_at(iter.headings(), t),
+        }
+    }
+
+    /// Write the frames from the beginning of the video to the end of the dive as
+    /// `overlay-00000.png`, ... into the directory, and return the number of frames
+    pub fn write_frames<P: AsRef<Path>>(
+        &self,
+        iter: &SrtIter,
+        dir: P,
+        rate: FrameRate,
+    ) -> Result<usize, Box<dyn std::error::Error + Sync + Send + 'static>> {
+        std::fs::create_dir_all(&dir)?;
+        let times = frame_times(iter.profile(), rate);
+        let max_depth = Self::gauge_depth(iter);
+        for (i, t) in times.iter().enumerate() {
+            self.frame(&self.values(iter, *t, max_depth))
+                .write_png(dir.as_ref().join(format!("overlay-{i:05}.png")))?;
+        }
+        Ok(times.len())
+    }
+}
+
+#[test]
+fn render_overlay() {
+    let overlay = Overlay {
+        width: 320,
+        height: 180,
+        ..Default::default()
+    };
+    let mut values = OverlayValues {
+        time: NaiveTime::from_hms_opt(10, 23, 45),
+        depth: Some(10.0),
+        max_depth: 20.0,
+        depth_unit: "m",
+        temperature: Some(28.0),
+        temperature_unit: "C",
+        heading: None,
+    };
+    let frame = overlay.frame(&values);
+    // the gauge is filled to the half
+    assert_eq!(frame.pixel(312, 60), overlay.colour);
+    assert_ne!(frame.pixel(312, 120), overlay.colour);
+    // the top left pixel of "1"
+    assert_eq!(frame.pixel(6, 6), TRANSPARENT);
+    assert_eq!(frame.pixel(8, 6), overlay.colour);
+    // no compass without the heading
+    assert_eq!(frame.pixel(20, 160), TRANSPARENT);
+
+    values.heading = Some(90.0);
+    let frame = overlay.frame(&values);
+    assert_eq!(frame.pixel(26, 160), overlay.marker);
+}
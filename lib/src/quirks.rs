@@ -0,0 +1,253 @@
+use std::path::Path;
+
+use crate::dive_site::split_csv_line;
+use crate::error::Fit2SrtError;
+
+// The quirks of the known devices in the CSV of `Quirks::from_csv`,
+// the converters of their logs write the pressure of the transmitter as `tank_pressure`
+// in the records
+const DEFAULT_QUIRKS: &str = "manufacturer,product,field,name,scale,offset,units
+suunto,*,tank_pressure,pressure,,,
+shearwater,*,tank_pressure,pressure,,,
+mares,*,tank_pressure,pressure,,,
+";
+
+/// A correction of a numeric field in the FIT files of a device
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldQuirk {
+    // The name in the FIT file
+    pub field: String,
+    // The name the generator knows, ex: depth
+    pub name: String,
+    // The value is `value * scale + offset`
+    pub scale: f64,
+    pub offset: f64,
+    // The unit after the correction, the one of the field is kept if it is empty
+    pub units: String,
+}
+
+/// The quirks of the FIT files of a manufacturer, or of one product if it is given
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceQuirks {
+    pub manufacturer: String,
+    pub product: Option<String>,
+    pub fields: Vec<FieldQuirk>,
+}
+
+/// The corrections of the fields by the devices,
+/// which are found with the `file_id` or the `device_info` messages of a FIT file
+#[derive(Clone, Debug)]
+pub struct Quirks {
+    devices: Vec<DeviceQuirks>,
+}
+
+impl Default for Quirks {
+    /// The quirks of Suunto, Shearwater and Mares
+    fn default() -> Self {
+        let mut quirks = Self {
+            devices: Vec::new(),
+        };
+        quirks
+            .add_csv(DEFAULT_QUIRKS)
+            .expect("the default quirks are valid");
+        quirks
+    }
+}
+
+impl Quirks {
+    /// Add the quirks of a device, they are applied over the ones added before
+    pub fn add(&mut self, device: DeviceQuirks) {
+        self.devices.push(device);
+    }
+
+    /// The corrections for the FIT files of a device,
+    /// the ones of the product are applied over the ones of the manufacturer
+    pub fn device(&self, manufacturer: Option<&str>, product: Option<&str>) -> Device<'_> {
+        let matches = |device: &&DeviceQuirks| {
+            manufacturer.is_some_and(|m| m.eq_ignore_ascii_case(&device.manufacturer))
+                && device
+                    .product
+                    .as_ref()
+                    .is_none_or(|p| product.is_some_and(|product| p.eq_ignore_ascii_case(product)))
+        };
+        let mut devices: Vec<&DeviceQuirks> = self.devices.iter().filter(matches).collect();
+        // stable, so the later added ones stay later
+        devices.sort_by_key(|device| device.product.is_some());
+        let mut fields: Vec<&FieldQuirk> = Vec::new();
+        for quirk in devices.into_iter().flat_map(|device| device.fields.iter()) {
+            fields.retain(|f| f.field != quirk.field);
+            fields.push(quirk);
+        }
+        Device { fields }
+    }
+
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send + 'static>> {
+        Ok(Self::from_csv(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Parse the CSV of `manufacturer,product,field,name,scale,offset,units`,
+    /// an empty or `*` product is for all products of the manufacturer,
+    /// the name, the scale, the offset and the units can be empty to keep them,
+    /// a line starting with `#` is a comment.
+    /// The quirks are applied over the default ones
+    pub fn from_csv(content: &str) -> Result<Self, Fit2SrtError> {
+        let mut quirks = Self::default();
+        quirks.add_csv(content)?;
+        Ok(quirks)
+    }
+
+    fn add_csv(&mut self, content: &str) -> Result<(), Fit2SrtError> {
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = split_csv_line(line);
+            if i == 0
+                && fields
+                    .first()
+                    .is_some_and(|f| f.eq_ignore_ascii_case("manufacturer"))
+            {
+                continue;
+            }
+            let column = |idx: usize| fields.get(idx).map(|f| f.trim()).unwrap_or_default();
+            let number = |idx: usize, default: f64| match column(idx) {
+                "" => Ok(default),
+                n => n.parse::<f64>().map_err(|_| {
+                    Fit2SrtError::QuirkError(format!("invalid number at line {}", i + 1))
+                }),
+            };
+            if column(0).is_empty() || column(2).is_empty() {
+                return Err(Fit2SrtError::QuirkError(format!(
+                    "missing manufacturer or field at line {}",
+                    i + 1
+                )));
+            }
+            let field = FieldQuirk {
+                field: column(2).to_string(),
+                name: match column(3) {
+                    "" => column(2).to_string(),
+                    name => name.to_string(),
+                },
+                scale: number(4, 1.0)?,
+                offset: number(5, 0.0)?,
+                units: column(6).to_string(),
+            };
+            let product = match column(1) {
+                "" | "*" => None,
+                product => Some(product.to_string()),
+            };
+            // the names are matched without the case like the devices of the files
+            let same_product = |p: &Option<String>| match (p, &product) {
+                (Some(p), Some(product)) => p.eq_ignore_ascii_case(product),
+                (p, product) => p.is_none() && product.is_none(),
+            };
+            match self.devices.iter_mut().find(|d| {
+                d.manufacturer.eq_ignore_ascii_case(column(0)) && same_product(&d.product)
+            }) {
+                Some(device) => device.fields.push(field),
+                None => self.add(DeviceQuirks {
+                    manufacturer: column(0).to_string(),
+                    product,
+                    fields: vec![field],
+                }),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The corrections for the FIT files of a device
+#[derive(Clone, Debug, Default)]
+pub struct Device<'a> {
+    fields: Vec<&'a FieldQuirk>,
+}
+
+impl Device<'_> {
+    /// The name, the value and the unit of a field after the quirk of the device,
+    /// then the value is in metres, Celsius or bar if it is in another unit of them
+    pub fn normalise<'b>(
+        &'b self,
+        field: &'b str,
+        value: f64,
+        units: &'b str,
+    ) -> (&'b str, f64, &'b str) {
+        let (name, value, units) = match self.fields.iter().find(|f| f.field == field) {
+            Some(quirk) => (
+                quirk.name.as_str(),
+                value * quirk.scale + quirk.offset,
+                if quirk.units.is_empty() {
+                    units
+                } else {
+                    quirk.units.as_str()
+                },
+            ),
+            None => (field, value, units),
+        };
+        let (value, units) = match units {
+            "ft" => (value * 0.3048, "m"),
+            "cm" => (value / 100.0, "m"),
+            "mm" => (value / 1000.0, "m"),
+            "F" | "°F" => ((value - 32.0) * 5.0 / 9.0, "C"),
+            "K" => (value - 273.15, "C"),
+            "°C" => (value, "C"),
+            "psi" => (value * 0.068_947_57, "bar"),
+            "kPa" => (value / 100.0, "bar"),
+            "mbar" => (value / 1000.0, "bar"),
+            "Pa" => (value / 100_000.0, "bar"),
+            units => (value, units),
+        };
+        (name, value, units)
+    }
+}
+
+#[test]
+fn device_quirks() {
+    let quirks = Quirks::from_csv(
+        "manufacturer,product,field,name,scale,offset,units
+# the depth in centimetres of all products
+acme,*,depth,,1,,cm
+acme,x1,enhanced_depth,depth,-1,,
+acme,x1,depth,depth,0.5,,ft
+",
+    )
+    .unwrap();
+    let device = quirks.device(Some("ACME"), Some("y2"));
+    assert_eq!(device.normalise("depth", 420.0, "m"), ("depth", 4.2, "m"));
+    assert_eq!(
+        device.normalise("enhanced_depth", -4.2, "m"),
+        ("enhanced_depth", -4.2, "m")
+    );
+    // the product is over the manufacturer
+    let device = quirks.device(Some("acme"), Some("x1"));
+    assert_eq!(
+        device.normalise("enhanced_depth", -4.2, "m"),
+        ("depth", 4.2, "m")
+    );
+    let (_, depth, units) = device.normalise("depth", 20.0, "m");
+    assert!((depth - 3.048).abs() < 1e-9 && units == "m");
+    // the units are normalised for any device
+    let device = quirks.device(None, None);
+    let (_, temperature, units) = device.normalise("temperature", 86.0, "F");
+    assert!((temperature - 30.0).abs() < 1e-9 && units == "C");
+    assert!(Quirks::from_csv("acme,,depth,,deep").is_err());
+
+    // the default quirks are kept under the ones of the file
+    let device = quirks.device(Some("mares"), None);
+    assert_eq!(
+        device.normalise("tank_pressure", 200_000.0, "Pa"),
+        ("pressure", 2.0, "bar")
+    );
+    let quirks = Quirks::from_csv("suunto,,tank_pressure,pressure,,,psi").unwrap();
+    let device = quirks.device(Some("suunto"), Some("eon"));
+    let (_, pressure, units) = device.normalise("tank_pressure", 3000.0, "bar");
+    assert!((pressure - 206.84271).abs() < 1e-3 && units == "bar");
+    // the lines of another case extend the same device
+    let quirks =
+        Quirks::from_csv("Suunto,,depth,,,,ft\nACME,X1,depth,,,,ft\nacme,x1,depth,,,,cm").unwrap();
+    let suunto = |d: &&DeviceQuirks| d.manufacturer.eq_ignore_ascii_case("suunto");
+    assert_eq!(quirks.devices.iter().filter(suunto).count(), 1);
+    let acme = |d: &&DeviceQuirks| d.manufacturer.eq_ignore_ascii_case("acme");
+    assert_eq!(quirks.devices.iter().filter(acme).count(), 1);
+}
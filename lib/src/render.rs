@@ -0,0 +1,370 @@
+use chrono::TimeDelta;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::timecode::FrameRate;
+
+/// Red, green, blue and alpha
+pub type Rgba = [u8; 4];
+
+pub const TRANSPARENT: Rgba = [0, 0, 0, 0];
+
+/// An RGBA image
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new(width: u32, height: u32, background: Rgba) -> Self {
+        Self {
+            width,
+            height,
+            pixels: background.repeat((width * height) as usize),
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Rgba {
+        let i = ((y * self.width + x) * 4) as usize;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Paint the colour over the pixel with its alpha
+    pub fn blend(&mut self, x: i64, y: i64, colour: Rgba) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 4) as usize;
+        let alpha = colour[3] as u32;
+        let dst_alpha = self.pixels[i + 3] as u32;
+        let out_alpha = alpha * 255 + dst_alpha * (255 - alpha);
+        if out_alpha == 0 {
+            return;
+        }
+        for (c, value) in colour.iter().take(3).enumerate() {
+            let src = *value as u32 * alpha * 255;
+            let dst = self.pixels[i + c] as u32 * dst_alpha * (255 - alpha);
+            self.pixels[i + c] = ((src + dst) / out_alpha) as u8;
+        }
+        self.pixels[i + 3] = (out_alpha / 255) as u8;
+    }
+
+    pub fn fill_rect(&mut self, x: i64, y: i64, width: i64, height: i64, colour: Rgba) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.blend(px, py, colour);
+            }
+        }
+    }
+
+    pub fn fill_circle(&mut self, (cx, cy): (f64, f64), radius: f64, colour: Rgba) {
+        let r = radius.ceil() as i64;
+        let (x0, y0) = (cx.round() as i64, cy.round() as i64);
+        for py in y0 - r..=y0 + r {
+            for px in x0 - r..=x0 + r {
+                let (dx, dy) = (px as f64 - cx, py as f64 - cy);
+                if dx * dx + dy * dy <= radius * radius {
+                    self.blend(px, py, colour);
+                }
+            }
+        }
+    }
+
+    /// A line of the width, the pixels of the joints are painted once
+    pub fn line(&mut self, points: &[(f64, f64)], width: f64, colour: Rgba) {
+        // the pixels of the stroke, not of the frame
+        let mut painted: Vec<(i64, i64)> = Vec::new();
+        let radius = width / 2.0;
+        let mut stamp = |(cx, cy): (f64, f64)| {
+            let r = radius.ceil() as i64;
+            for py in cy.round() as i64 - r..=cy.round() as i64 + r {
+                for px in cx.round() as i64 - r..=cx.round() as i64 + r {
+                    let (dx, dy) = (px as f64 - cx, py as f64 - cy);
+                    if px >= 0
+                        && py >= 0
+                        && px < self.width as i64
+                        && py < self.height as i64
+                        && dx * dx + dy * dy <= radius * radius + 0.25
+                    {
+                        painted.push((py, px));
+                    }
+                }
+            }
+        };
+        for w in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+            let steps = ((x1 - x0).hypot(y1 - y0) * 2.0).ceil().max(1.0) as usize;
+            for i in 0..=steps {
+                let k = i as f64 / steps as f64;
+                stamp((x0 + (x1 - x0) * k, y0 + (y1 - y0) * k));
+            }
+        }
+        if let [point] = points {
+            stamp(*point);
+        }
+        painted.sort_unstable();
+        painted.dedup();
+        for (y, x) in painted {
+            self.blend(x, y, colour);
+        }
+    }
+
+    pub fn write_png<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static>> {
+        let fp = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(fp, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+}
+
+/// The value at the time, linear between the samples
+pub fn value_at(profile: &[(TimeDelta, f64)], t: TimeDelta) -> Option<f64> {
+    let idx = profile.partition_point(|(time, _)| *time <= t);
+    match (
+        idx.checked_sub(1).map(|i| profile[i]),
+        profile.get(idx).copied(),
+    ) {
+        (Some((t0, d0)), Some((t1, d1))) => {
+            let k = (t - t0).num_milliseconds() as f64 / (t1 - t0).num_milliseconds() as f64;
+            Some(d0 + (d1 - d0) * k)
+        }
+        (Some((_, d)), None) | (None, Some((_, d))) => Some(d),
+        (None, None) => None,
+    }
+}
+
+/// The times of the frames from the beginning of the video to the end of the dive
+pub fn frame_times(profile: &[(TimeDelta, f64)], rate: FrameRate) -> Vec<TimeDelta> {
+    let Some((end, _)) = profile.last() else {
+        return Vec::new();
+    };
+    (0..=rate.frame(end))
+        .map(|frame| rate.frame_time(frame))
+        .collect()
+}
+
+/// A mini chart of the dive profile with a marker on the current depth
+#[derive(Clone, Debug)]
+pub struct ProfileChart {
+    pub width: u32,
+    pub height: u32,
+    pub margin: u32,
+    pub background: Rgba,
+    pub line: Rgba,
+    pub line_width: f64,
+    pub marker: Rgba,
+    pub marker_radius: f64,
+}
+
+impl Default for ProfileChart {
+    fn default() -> Self {
+        Self {
+            width: 320,
+            height: 120,
+            margin: 8,
+            background: TRANSPARENT,
+            line: [255, 255, 255, 255],
+            line_width: 2.0,
+            marker: [255, 64, 64, 255],
+            marker_radius: 4.0,
+        }
+    }
+}
+
+impl ProfileChart {
+    /// The positions on the chart of the depths at the times
+    fn scale(&self, profile: &[(TimeDelta, f64)]) -> impl Fn(TimeDelta, f64) -> (f64, f64) {
+        let start = profile.first().map_or(TimeDelta::zero(), |(t, _)| *t);
+        let end = profile.last().map_or(TimeDelta::zero(), |(t, _)| *t);
+        let duration = (end - start).num_milliseconds().max(1) as f64;
+        let max_depth = profile
+            .iter()
+            .map(|(_, d)| *d)
+            .fold(0f64, f64::max)
+            .max(1.0);
+        let (width, height, margin) = (self.width as f64, self.height as f64, self.margin as f64);
+        move |t, depth| {
+            let k = ((t - start).num_milliseconds() as f64 / duration).clamp(0.0, 1.0);
+            (
+                margin + k * (width - 2.0 * margin),
+                margin + depth.max(0.0) / max_depth * (height - 2.0 * margin),
+            )
+        }
+    }
+
+    fn points(&self, profile: &[(TimeDelta, f64)]) -> Vec<(f64, f64)> {
+        let point = self.scale(profile);
+        profile.iter().map(|(t, d)| point(*t, *d)).collect()
+    }
+
+    /// The chart without the marker
+    pub fn chart(&self, profile: &[(TimeDelta, f64)]) -> Frame {
+        let mut frame = Frame::new(self.width, self.height, self.background);
+        frame.line(&self.points(profile), self.line_width, self.line);
+        frame
+    }
+
+    /// The chart with the marker at the time
+    pub fn frame(&self, chart: &Frame, profile: &[(TimeDelta, f64)], t: TimeDelta) -> Frame {
+        let mut frame = chart.clone();
+        if let Some(depth) = value_at(profile, t) {
+            frame.fill_circle(
+                self.scale(profile)(t, depth),
+                self.marker_radius,
+                self.marker,
+            );
+        }
+        frame
+    }
+
+    /// Write the frames as `profile-00000.png`, ... into the directory, and return the number of frames
+    pub fn write_frames<P: AsRef<Path>>(
+        &self,
+        profile: &[(TimeDelta, f64)],
+        dir: P,
+        rate: FrameRate,
+    ) -> Result<usize, Box<dyn std::error::Error + Sync + Send + 'static>> {
+        std::fs::create_dir_all(&dir)?;
+        let chart = self.chart(profile);
+        let times = frame_times(profile, rate);
+        for (i, t) in times.iter().enumerate() {
+            self.frame(&chart, profile, *t)
+                .write_png(dir.as_ref().join(format!("profile-{i:05}.png")))?;
+        }
+        Ok(times.len())
+    }
+
+    /// An ASS subtitle drawing the chart with `\p1` at the position of the video,
+    /// and a marker moving on it every interval
+    pub fn ass(
+        &self,
+        profile: &[(TimeDelta, f64)],
+        video_size: (u32, u32),
+        position: (u32, u32),
+        interval: TimeDelta,
+    ) -> String {
+        let mut ass = format!(
+            "[Script Info]
+ScriptType: v4.00+
+PlayResX: {}
+PlayResY: {}
+WrapStyle: 2
+ScaledBorderAndShadow: yes
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Profile,Arial,20,{},&H00FFFFFF,{},{},0,0,0,0,100,100,0,0,1,{:.1},0,7,0,0,0,1
+Style: Marker,Arial,20,{},&H00FFFFFF,&HFF000000,&HFF000000,0,0,0,0,100,100,0,0,1,0,0,7,0,0,0,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+",
+            video_size.0,
+            video_size.1,
+            ass_colour(self.background),
+            ass_colour(self.line),
+            ass_colour(self.background),
+            self.line_width / 2.0,
+            ass_colour(self.marker),
+        );
+        let (Some((start, _)), Some((end, _))) = (profile.first(), profile.last()) else {
+            return ass;
+        };
+        let (x, y) = position;
+
+        // the area between the surface and the profile
+        let points = self.points(profile);
+        let surface = self.margin as f64;
+        let mut drawing = format!("m {:.0} {surface:.0}", points[0].0);
+        for (px, py) in points.iter() {
+            drawing += &format!(" l {px:.0} {py:.0}");
+        }
+        drawing += &format!(" l {:.0} {surface:.0}", points[points.len() - 1].0);
+        ass += &format!(
+            "Dialogue: 0,{},{},Profile,,0,0,0,,{{\\pos({x},{y})}}{{\\p1}}{drawing}{{\\p0}}\n",
+            ass_time(start),
+            ass_time(end),
+        );
+
+        let point = self.scale(profile);
+        let r = self.marker_radius;
+        let mut t = *start;
+        while t < *end {
+            let next = (t + interval).min(*end);
+            if let Some(depth) = value_at(profile, t) {
+                let (px, py) = point(t, depth);
+                ass += &format!(
+                    "Dialogue: 1,{},{},Marker,,0,0,0,,{{\\pos({:.0},{:.0})}}{{\\p1}}m {} {} l {r} {} l {r} {r} l {} {r}{{\\p0}}\n",
+                    ass_time(&t),
+                    ass_time(&next),
+                    x as f64 + px,
+                    y as f64 + py,
+                    -r,
+                    -r,
+                    -r,
+                    -r,
+                );
+            }
+            t = next;
+        }
+        ass
+    }
+}
+
+/// `&HAABBGGRR`, the alpha of ASS is the transparency
+fn ass_colour([r, g, b, a]: Rgba) -> String {
+    format!("&H{:02X}{b:02X}{g:02X}{r:02X}", 255 - a)
+}
+
+/// `0:00:00.00`
+fn ass_time(t: &TimeDelta) -> String {
+    let cs = t.num_milliseconds().max(0) / 10;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        cs / 360000,
+        cs / 6000 % 60,
+        cs / 100 % 60,
+        cs % 100
+    )
+}
+
+#[test]
+fn render_profile() {
+    let profile: Vec<(TimeDelta, f64)> = [0.0, 5.0, 10.0, 5.0, 0.0]
+        .into_iter()
+        .enumerate()
+        .map(|(i, d)| (TimeDelta::seconds(i as i64 * 10), d))
+        .collect();
+    assert_eq!(value_at(&profile, TimeDelta::seconds(5)), Some(2.5));
+
+    let chart = ProfileChart::default();
+    let frame = chart.chart(&profile);
+    assert_eq!(frame.pixel(0, 0), TRANSPARENT);
+    // the deepest point is at the bottom centre
+    assert_eq!(frame.pixel(160, 112), [255, 255, 255, 255]);
+    let frame = chart.frame(&frame, &profile, TimeDelta::seconds(20));
+    assert_eq!(frame.pixel(160, 112), [255, 64, 64, 255]);
+
+    let rate = "1".parse().unwrap();
+    assert_eq!(frame_times(&profile, rate).len(), 41);
+
+    let ass = chart.ass(&profile, (1920, 1080), (20, 940), TimeDelta::seconds(10));
+    assert!(ass.contains(
+        "Dialogue: 0,0:00:00.00,0:00:40.00,Profile,,0,0,0,,{\\pos(20,940)}{\\p1}m 8 8 l 8 8 l 84 60 l 160 112 l 236 60 l 312 8 l 312 8{\\p0}\n"
+    ));
+    assert_eq!(ass.matches(",Marker,").count(), 4);
+}
@@ -0,0 +1,157 @@
+use chrono::TimeDelta;
+
+use crate::locale::Locale;
+use crate::srt_iter::Cue;
+
+// The diver is on the surface above it, and the stop is over
+const SURFACE: f64 = 1.0;
+
+/// The countdown of the safety stop in a band of depth after the deepest part of the dive
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SafetyStop {
+    // The shallow and the deep end of the band
+    pub top: f64,
+    pub bottom: f64,
+    pub duration: TimeDelta,
+}
+
+impl Default for SafetyStop {
+    fn default() -> Self {
+        Self {
+            top: 3.0,
+            bottom: 6.0,
+            duration: TimeDelta::minutes(3),
+        }
+    }
+}
+
+impl SafetyStop {
+    /// The countdown from the events of the dive computer, `true` for the start of a stop,
+    /// `false` for the completion or the break of it
+    pub fn from_events(&self, events: &[(TimeDelta, bool)], locale: &Locale) -> Vec<Cue> {
+        let mut cues = Vec::new();
+        for (i, (start, started)) in events.iter().enumerate() {
+            if !started {
+                continue;
+            }
+            let end = events[i + 1..]
+                .first()
+                .map_or(*start + self.duration, |(t, _)| *t)
+                .min(*start + self.duration);
+            countdown(&mut cues, *start, end, self.duration, locale);
+        }
+        cues
+    }
+
+    /// The countdown while the diver is in the band after the deepest sample,
+    /// it pauses outside the band and ends on the surface, or if the dive is not deeper than the band
+    pub fn from_depths(&self, profile: &[(TimeDelta, f64)], locale: &Locale) -> Vec<Cue> {
+        let mut cues = Vec::new();
+        let Some(deepest) = profile
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1))
+            .filter(|(_, (_, depth))| *depth > self.bottom)
+            .map(|(idx, _)| idx)
+        else {
+            return cues;
+        };
+        let mut remaining = self.duration;
+        for w in profile[deepest..].windows(2) {
+            let ((t0, d0), (t1, _)) = (w[0], w[1]);
+            if d0 < SURFACE || remaining <= TimeDelta::zero() {
+                break;
+            }
+            if (self.top..=self.bottom).contains(&d0) {
+                remaining = countdown(&mut cues, t0, t1, remaining, locale);
+            }
+        }
+        cues
+    }
+}
+
+/// Add the cues of the remaining time from the start to the end, and return the time left
+fn countdown(
+    cues: &mut Vec<Cue>,
+    mut start: TimeDelta,
+    end: TimeDelta,
+    mut remaining: TimeDelta,
+    locale: &Locale,
+) -> TimeDelta {
+    let second = TimeDelta::seconds(1);
+    while start < end && remaining > TimeDelta::zero() {
+        // the remaining time is shown in whole seconds rounded up
+        let shown = TimeDelta::seconds((remaining.num_milliseconds() + 999) / 1000);
+        let step = (end - start).min(remaining - (shown - second));
+        let text = label(shown, locale);
+        match cues.last_mut() {
+            Some(cue) if cue.end == start && cue.text == text => cue.end = start + step,
+            _ => cues.push(Cue {
+                start,
+                end: start + step,
+                text,
+            }),
+        }
+        start += step;
+        remaining -= step;
+    }
+    remaining
+}
+
+/// The safety stop with the remaining time in whole seconds rounded up, ex: Safety stop 2:59
+pub(crate) fn label(remaining: TimeDelta, locale: &Locale) -> String {
+    let shown = TimeDelta::seconds((remaining.num_milliseconds() + 999) / 1000);
+    format!(
+        "{} {}:{:02}",
+        locale.text("Safety stop"),
+        shown.num_minutes(),
+        shown.num_seconds() % 60
+    )
+}
+
+#[test]
+fn safety_stop_countdown() {
+    let stop = SafetyStop {
+        duration: TimeDelta::seconds(3),
+        ..Default::default()
+    };
+    // 2 seconds in the band, a second above it, then back in the band
+    let profile: Vec<(TimeDelta, f64)> = [8.0, 5.0, 5.0, 2.5, 4.5, 4.5, 4.0, 0.5]
+        .into_iter()
+        .enumerate()
+        .map(|(i, d)| (TimeDelta::seconds(i as i64), d))
+        .collect();
+    let texts: Vec<(i64, i64, String)> = stop
+        .from_depths(&profile, &Locale::default())
+        .into_iter()
+        .map(|c| (c.start.num_seconds(), c.end.num_seconds(), c.text))
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            (1, 2, "Safety stop 0:03".to_string()),
+            (2, 3, "Safety stop 0:02".to_string()),
+            (4, 5, "Safety stop 0:01".to_string()),
+        ]
+    );
+    // no stop for a shallow dive
+    assert!(stop
+        .from_depths(&profile[3..], &Locale::default())
+        .is_empty());
+
+    let cues = stop.from_events(
+        &[
+            (TimeDelta::seconds(10), true),
+            (TimeDelta::seconds(12), false),
+        ],
+        &Locale::new(crate::Lang::Zh),
+    );
+    assert_eq!(cues.len(), 2);
+    assert_eq!(cues[1].text, "安全停留 0:02");
+    assert_eq!(cues[1].end, TimeDelta::seconds(12));
+    // the label of the start is of the duration of the stop
+    assert_eq!(
+        label(TimeDelta::seconds(90), &Locale::new(crate::Lang::Ja)),
+        "安全停止 1:30"
+    );
+}
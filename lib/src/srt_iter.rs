@@ -1,283 +1,1707 @@
-use chrono::{DateTime, Local, NaiveTime, TimeDelta, TimeZone, Timelike};
-use fitparser::Value;
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Timelike};
+use fitparser::{FitDataRecord, Value};
 use std::collections::VecDeque;
 use std::fs::File;
 use std::path::Path;
+use std::str::FromStr;
 
-use crate::summary::Summary;
+use crate::ccr::{self, LoopChange};
+use crate::chapter::Chapter;
+use crate::dive_site::DiveSites;
+use crate::error::Fit2SrtError;
+use crate::event::Event;
+use crate::filter::{self, Filter};
+use crate::heading;
+use crate::interpolation::Interpolation;
+use crate::locale::Locale;
+use crate::metric::Metric;
+use crate::quirks::{Device, Quirks};
+use crate::safety_stop::{self, SafetyStop};
+use crate::subtitle::{self, Overlap};
+use crate::summary::{
+    default_template, gas_name, number_dives, semicircles_to_degrees, Summary, SummaryCue,
+    SummaryPosition,
+};
+use crate::thermocline::Thermocline;
+use crate::timecode::{FrameRate, TimeFormat};
+use crate::waypoint::Waypoint;
 
 type SrtString = String;
 
-#[derive(Copy, Clone)]
+// A dive starts when the diver is deeper than it
+const DESCENT_DEPTH: f64 = 1.2;
+
+// The duration of the cues of the events, the thermocline and the turns
+const EXTRA_CUE_DURATION: TimeDelta = TimeDelta::seconds(5);
+
+#[derive(Clone)]
 pub struct SrtGenerator {
-    field: &'static str,
-    tick: f64,
+    metrics: Vec<Metric>,
+    // The thresholds to emit a cue, other than the default of the metric
+    thresholds: Vec<(Metric, f64)>,
+    // The filters of the samples of the metrics, applied in order before the cues
+    filters: Vec<(Metric, Vec<Filter>)>,
+    // The least temperature drop per metre of a thermocline, None to skip the detection
+    thermocline: Option<f64>,
+    // The window to smooth the heading
+    heading_smoothing: TimeDelta,
+    // The least angle in degrees to be a turn around, None to skip the detection
+    turnaround: Option<f64>,
+    // The kinds of `event` messages shown as cues
+    events: Vec<Event>,
+    // The countdown of the safety stop, None to skip it
+    safety_stop: Option<SafetyStop>,
+    // The `strftime` format of the clock metric
+    clock_format: String,
 
     // These are used when a video recording before under water
-    start_time_secs: u32,
-    after_time_secs: u32,
-    before_time_secs: u32,
+    start_time: TimeDelta,
+    after_time: TimeDelta,
+    before_time: TimeDelta,
+
+    time_format: TimeFormat,
+
+    // Resample values to the interval between samples
+    interpolation: Option<(Interpolation, TimeDelta)>,
+
+    // The cue of the dive summary, None to skip it
+    summary: Option<SummaryCue>,
+
+    // Name the location of the dive with the sites
+    dive_sites: Option<DiveSites>,
+
+    // The corrections of the fields by the devices
+    quirks: Quirks,
+
+    // The language and the formats of the texts
+    locale: Locale,
 }
 
 impl Default for SrtGenerator {
     fn default() -> Self {
         Self {
-            field: "depth",
-            tick: 0.1,
-            start_time_secs: 0,
-            after_time_secs: 0,
-            before_time_secs: 0,
+            metrics: vec![Metric::Depth],
+            thresholds: Vec::new(),
+            filters: Vec::new(),
+            thermocline: None,
+            heading_smoothing: TimeDelta::seconds(5),
+            turnaround: None,
+            events: Vec::new(),
+            safety_stop: None,
+            clock_format: Metric::Clock.default_unit().to_string(),
+            start_time: TimeDelta::zero(),
+            after_time: TimeDelta::zero(),
+            before_time: TimeDelta::zero(),
+            time_format: TimeFormat::default(),
+            interpolation: None,
+            summary: Some(SummaryCue::default()),
+            dive_sites: None,
+            quirks: Quirks::default(),
+            locale: Locale::default(),
         }
     }
 }
 
 impl SrtGenerator {
+    pub fn after(&mut self, t: TimeDelta) {
+        self.after_time += t;
+    }
+
     pub fn after_hour(&mut self, h: u32) {
-        self.after_time_secs += h * 60 * 60;
+        self.after_time += TimeDelta::hours(h as i64);
     }
 
     pub fn after_minute(&mut self, m: u32) {
-        self.after_time_secs += m * 60;
+        self.after_time += TimeDelta::minutes(m as i64);
     }
 
     pub fn after_second(&mut self, s: u32) {
-        self.after_time_secs += s;
+        self.after_time += TimeDelta::seconds(s as i64);
+    }
+
+    pub fn after_millisecond(&mut self, ms: u32) {
+        self.after_time += TimeDelta::milliseconds(ms as i64);
+    }
+
+    pub fn before(&mut self, t: TimeDelta) {
+        self.before_time += t;
     }
 
     pub fn before_hour(&mut self, h: u32) {
-        self.before_time_secs += h * 60 * 60;
+        self.before_time += TimeDelta::hours(h as i64);
     }
 
     pub fn before_minute(&mut self, m: u32) {
-        self.before_time_secs += m * 60;
+        self.before_time += TimeDelta::minutes(m as i64);
     }
 
     pub fn before_second(&mut self, s: u32) {
-        self.before_time_secs += s;
+        self.before_time += TimeDelta::seconds(s as i64);
+    }
+
+    pub fn before_millisecond(&mut self, ms: u32) {
+        self.before_time += TimeDelta::milliseconds(ms as i64);
+    }
+
+    pub fn starting(&mut self, t: TimeDelta) {
+        self.start_time += t;
     }
 
     pub fn starting_hour(&mut self, h: u32) {
-        self.start_time_secs += h * 60 * 60;
+        self.start_time += TimeDelta::hours(h as i64);
     }
 
     pub fn starting_minute(&mut self, m: u32) {
-        self.start_time_secs += m * 60;
+        self.start_time += TimeDelta::minutes(m as i64);
     }
 
     pub fn starting_second(&mut self, s: u32) {
-        self.start_time_secs += s;
+        self.start_time += TimeDelta::seconds(s as i64);
+    }
+
+    pub fn starting_millisecond(&mut self, ms: u32) {
+        self.start_time += TimeDelta::milliseconds(ms as i64);
+    }
+
+    /// Snap the start and the end of cues to the frames of the video
+    pub fn frame_rate(&mut self, rate: FrameRate) {
+        self.time_format = TimeFormat::SrtFrames(rate);
+    }
+
+    /// Write the times of cues as SMPTE timecode of the video frame rate
+    pub fn smpte_timecode(&mut self, rate: FrameRate) {
+        self.time_format = TimeFormat::Smpte(rate);
+    }
+
+    /// The values shown in the cues, in the order of the list
+    pub fn metrics(&mut self, metrics: Vec<Metric>) {
+        self.metrics = metrics;
+    }
+
+    /// Emit a cue of the metric only if the value changes more than the threshold
+    pub fn threshold(&mut self, metric: Metric, threshold: f64) {
+        self.thresholds.retain(|(m, _)| *m != metric);
+        self.thresholds.push((metric, threshold));
+    }
+
+    /// Filter the samples of the metric before the cues are generated,
+    /// ex: a median to remove the spikes of the depth, the filters are applied in order
+    pub fn filters(&mut self, metric: Metric, filters: Vec<Filter>) {
+        self.filters.retain(|(m, _)| *m != metric);
+        self.filters.push((metric, filters));
+    }
+
+    /// Detect the thermocline where the temperature drops at least the gradient (per metre),
+    /// a cue is shown when the diver descends through it
+    pub fn thermocline(&mut self, min_gradient: Option<f64>) {
+        self.thermocline = min_gradient;
+    }
+
+    /// Smooth the noise of the compass with the circular mean of the headings in the window
+    pub fn smooth_heading(&mut self, window: TimeDelta) {
+        self.heading_smoothing = window;
+    }
+
+    /// Show a cue when the heading turns at least the angle (degrees) in 30 seconds,
+    /// ex: the turn around point of a navigation course
+    pub fn turnaround(&mut self, min_angle: Option<f64>) {
+        self.turnaround = min_angle;
+    }
+
+    /// Show the `event` messages of the kinds as short cues over the values,
+    /// ex: alarms, safety stops and gas switches
+    pub fn events(&mut self, events: Vec<Event>) {
+        self.events = events;
+    }
+
+    /// Count down the safety stop from the events of the dive computer,
+    /// or while the diver is in the band of depth after the deepest part of the dive
+    pub fn safety_stop(&mut self, stop: Option<SafetyStop>) {
+        self.safety_stop = stop;
+    }
+
+    /// The format of the clock metric, ex: %H:%M:%S, %I:%M %p
+    pub fn clock_format(&mut self, format: String) {
+        self.clock_format = format;
+    }
+
+    /// Emit cues on a fixed interval with values interpolated between the samples,
+    /// a cue is only emitted when the value shown changes
+    pub fn interpolate(&mut self, method: Interpolation, interval: TimeDelta) {
+        self.interpolation = Some((method, interval));
     }
+
+    /// Set the template, timing and position of the summary cue, or None to skip it
+    pub fn summary(&mut self, cue: Option<SummaryCue>) {
+        self.summary = cue;
+    }
+
+    /// Name the location in the summary with the nearest dive site,
+    /// the coordinates are shown if there is no site in the tolerance
+    pub fn dive_sites(&mut self, sites: Option<DiveSites>) {
+        self.dive_sites = sites;
+    }
+
+    /// Correct the names, the values and the units of the fields by the devices of the FIT files
+    pub fn quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    /// Write the labels, the numbers and the dates in the language and the formats of the locale,
+    /// the default template of the summary is also in the language
+    pub fn locale(&mut self, locale: Locale) {
+        self.locale = locale;
+    }
+
+    pub fn format_time(&self, delta: &TimeDelta) -> String {
+        self.time_format.format(delta)
+    }
+
     pub fn open_file(
-        self,
+        &self,
         fp: &mut File,
     ) -> Result<SrtIter, Box<dyn std::error::Error + Sync + Send + 'static>> {
-        let mut start_time: Option<DateTime<Local>> = None;
-        let mut previous_value = f64::NAN;
-        let mut data = VecDeque::new();
-        let mut unit = "".to_string();
-        let mut before = true;
-        let mut previous_time = None;
+        let mut iter = self.parse_records(&fitparser::from_reader(fp)?)?;
+        self.finish(&mut iter);
+        Ok(iter)
+    }
+
+    /// Name the dive site and add the summary cue
+    fn finish(&self, iter: &mut SrtIter) {
+        self.name_site(&mut iter.summary);
+        if let Some(cue) = &self.summary {
+            iter.add_summary(cue);
+        }
+    }
+
+    /// Name the location of the summary with the nearest dive site
+    fn name_site(&self, summary: &mut Summary) {
+        if let (Some(sites), Some((lat, long))) = (&self.dive_sites, summary.location()) {
+            summary.site = sites.nearest(lat, long).map(|site| site.name.clone());
+        }
+    }
+
+    /// The window of `after` and `before` in the local time of the file, `after` is the time
+    /// nearest to the first time of the file and `before` is the next time after it,
+    /// so a dive across midnight is kept after 00:00
+    fn window(&self, first: &DateTime<Local>) -> Window {
+        let after = (!self.after_time.is_zero()).then(|| nearest_time(first, self.after_time));
+        let before = (!self.before_time.is_zero()).then(|| match after {
+            Some(after) => {
+                let before = after.date().and_time(NaiveTime::MIN) + self.before_time;
+                if before <= after {
+                    before + TimeDelta::days(1)
+                } else {
+                    before
+                }
+            }
+            None => nearest_time(first, self.before_time),
+        });
+        Window { after, before }
+    }
+
+    fn parse_records(
+        &self,
+        records: &[FitDataRecord],
+    ) -> Result<SrtIter, Box<dyn std::error::Error + Sync + Send + 'static>> {
+        let mut samples: Vec<Vec<(DateTime<Local>, f64)>> = vec![Vec::new(); self.metrics.len()];
+        let mut units: Vec<String> = vec![String::new(); self.metrics.len()];
+        // the time and the event timestamp of `hr` messages to place the heart beats on
+        let mut hr_anchor: Option<(DateTime<Local>, f64)> = None;
+        // the depth and the temperature of records for the dive profile and the thermocline
+        let mut profile: Vec<(DateTime<Local>, f64, Option<f64>)> = Vec::new();
+        // the `heading` fields of any message with a timestamp, ex: `gps_metadata`
+        let mut headings: Vec<(DateTime<Local>, f64)> = Vec::new();
+        // the tank pressures of `tank_update` messages or of records in the window
+        let mut pressures: Vec<f64> = Vec::new();
+        // the texts of `event` messages with their data, and the gases by their index
+        // to name a gas switch
+        let mut events: Vec<(DateTime<Local>, Event, String, Option<u32>)> = Vec::new();
+        let mut gas_indices: Vec<(u32, (u8, u8))> = Vec::new();
+        // the starts of laps and the bookmarks of the diver for the chapters
+        let mut marks: Vec<(DateTime<Local>, &'static str)> = Vec::new();
+        // the starts and the ends of the safety stops of the dive computer
+        let mut stop_events: Vec<(DateTime<Local>, bool)> = Vec::new();
+        // the changes of the loop of a rebreather, and the setpoints of `dive_settings`,
+        // or the defaults of the dive computers
+        let mut loop_changes: Vec<(DateTime<Local>, LoopChange)> = Vec::new();
+        let (mut low_setpoint, mut high_setpoint) = (0.7, 1.3);
+        // the first time of the file and the descent, out of the window of `after` and `before`,
+        // and the first and the last time of the records in the window
+        let mut file_start: Option<DateTime<Local>> = None;
+        let mut window: Option<(DateTime<Local>, DateTime<Local>)> = None;
+        let mut descent: Option<DateTime<Local>> = None;
         let mut summary = Summary::default();
+        // the quirks of the device, found with `file_id` or `device_info`
+        let mut device = Device::default();
+        // the developer fields of `field_description` messages
+        let mut descriptions: Vec<FieldDescription> = Vec::new();
+        // the semicircles of the entry and the exit
+        let mut entry = (None, None);
+        let mut exit = (None, None);
 
-        for record in fitparser::from_reader(fp)? {
+        let bounds = records
+            .iter()
+            .flat_map(|record| record.fields())
+            .find_map(|field| match (field.name(), field.value()) {
+                ("timestamp", Value::Timestamp(ts)) => Some(self.window(ts)),
+                _ => None,
+            })
+            .unwrap_or_default();
+        for record in records {
             let mut timestamp: Option<DateTime<Local>> = None;
-            let mut value = 0f64;
-            let mut has_depth = false;
+            let mut values: Vec<Option<f64>> = vec![None; self.metrics.len()];
+            let mut depth = None;
+            let mut temperature = None;
+            let mut heading = None;
+            let mut ppo2 = None;
+            let mut pressure = None;
             match record.kind() {
                 fitparser::profile::field_types::MesgNum::DiveSummary => {
+                    for field in record.fields() {
+                        let normalised = value_f64(field.value())
+                            .map(|v| device.normalise(field.name(), v, field.units()));
+                        match (field.name(), normalised) {
+                            (_, Some(("avg_depth", d, unit))) => {
+                                summary.avg_depth = Some(d);
+                                summary.set_unit(unit)?
+                            }
+                            (_, Some(("max_depth", d, unit))) => {
+                                summary.max_depth = Some(d);
+                                summary.set_unit(unit)?
+                            }
+                            ("dive_number", _) => {
+                                summary.dive_number = value_u32(field.value());
+                            }
+                            ("surface_interval", _) => {
+                                summary.surface_interval = value_u32(field.value());
+                            }
+                            ("start_n2", _) => {
+                                summary.start_n2 = value_u32(field.value());
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                fitparser::profile::field_types::MesgNum::TankSummary => {
+                    // the pressures of the first tank
+                    if summary.start_pressure.is_some() || summary.end_pressure.is_some() {
+                        continue;
+                    }
+                    for field in record.fields() {
+                        let normalised = value_f64(field.value())
+                            .map(|v| device.normalise(field.name(), v, field.units()));
+                        match normalised {
+                            Some(("start_pressure", p, _)) => summary.start_pressure = Some(p),
+                            Some(("end_pressure", p, _)) => summary.end_pressure = Some(p),
+                            _ => (),
+                        }
+                    }
+                }
+                fitparser::profile::field_types::MesgNum::DiveSettings => {
+                    for field in record.fields() {
+                        match (field.name(), value_f64(field.value())) {
+                            ("ccr_low_setpoint", Some(setpoint)) => low_setpoint = setpoint,
+                            ("ccr_high_setpoint", Some(setpoint)) => high_setpoint = setpoint,
+                            _ => (),
+                        }
+                    }
+                }
+                fitparser::profile::field_types::MesgNum::DiveGas => {
+                    let mut o2 = None;
+                    let mut he = 0;
+                    let mut enabled = true;
+                    let mut index = None;
                     for field in record.fields() {
                         match field.name() {
-                            "avg_depth" => {
-                                if let fitparser::Value::Float64(d) = field.value() {
-                                    summary.avg_depth = Some(*d);
-                                    summary.set_unit(field.units())?
-                                }
+                            "message_index" => index = value_u32(field.value()),
+                            "oxygen_content" => o2 = value_u32(field.value()),
+                            "helium_content" => he = value_u32(field.value()).unwrap_or(0),
+                            "status" => {
+                                enabled = value_string(field.value()).as_deref() == Some("enabled")
                             }
-                            "max_depth" => {
-                                if let fitparser::Value::Float64(d) = field.value() {
-                                    summary.max_depth = Some(*d);
-                                    summary.set_unit(field.units())?
-                                }
+                            _ => (),
+                        }
+                    }
+                    if let (Some(o2), Some(index)) = (o2, index) {
+                        gas_indices.push((index, (o2 as u8, he as u8)));
+                    }
+                    if let (Some(o2), true) = (o2, enabled) {
+                        let gas = (o2 as u8, he as u8);
+                        if !summary.gases.contains(&gas) {
+                            summary.gases.push(gas);
+                        }
+                    }
+                }
+                fitparser::profile::field_types::MesgNum::FileId => {
+                    for field in record.fields() {
+                        match field.name() {
+                            "manufacturer" => summary.manufacturer = value_string(field.value()),
+                            "product" | "garmin_product" | "product_name" => {
+                                summary.product = value_string(field.value())
                             }
+                            "serial_number" => summary.serial_number = value_u32(field.value()),
                             _ => (),
                         }
                     }
+                    device = self
+                        .quirks
+                        .device(summary.manufacturer.as_deref(), summary.product.as_deref());
                 }
-                fitparser::profile::field_types::MesgNum::Session => {
+                fitparser::profile::field_types::MesgNum::DeviceInfo => {
+                    // the creator of the file, if `file_id` does not tell
+                    let mut creator = false;
+                    let mut manufacturer = None;
+                    let mut product = None;
+                    for field in record.fields() {
+                        match field.name() {
+                            "device_index" => creator = value_u32(field.value()) == Some(0),
+                            "manufacturer" => manufacturer = value_string(field.value()),
+                            "product" | "garmin_product" | "product_name" => {
+                                product = product.or(value_string(field.value()))
+                            }
+                            _ => (),
+                        }
+                    }
+                    if creator && (summary.manufacturer.is_none() || summary.product.is_none()) {
+                        summary.manufacturer = summary.manufacturer.take().or(manufacturer);
+                        summary.product = summary.product.take().or(product);
+                        device = self
+                            .quirks
+                            .device(summary.manufacturer.as_deref(), summary.product.as_deref());
+                    }
+                }
+                fitparser::profile::field_types::MesgNum::FieldDescription => {
+                    let mut description = FieldDescription {
+                        index: 0,
+                        number: 0,
+                        name: String::new(),
+                    };
                     for field in record.fields() {
                         match field.name() {
-                            "start_position_lat" | "end_position_lat" => {
+                            "developer_data_index" => {
+                                description.index = value_u32(field.value()).unwrap_or(0) as u8
+                            }
+                            "field_definition_number" => {
+                                description.number = value_u32(field.value()).unwrap_or(0) as u8
+                            }
+                            "field_name" => {
+                                description.name = value_string(field.value()).unwrap_or_default()
+                            }
+                            _ => (),
+                        }
+                    }
+                    descriptions
+                        .retain(|d| (d.index, d.number) != (description.index, description.number));
+                    descriptions.push(description);
+                }
+                fitparser::profile::field_types::MesgNum::Session => {
+                    for field in record.fields() {
+                        if let Some((index, name, value, unit)) =
+                            developer_field(&descriptions, field)
+                        {
+                            let metric = Metric::Developer {
+                                index: Some(index),
+                                name: name.to_string(),
+                            };
+                            summary.developer_fields.push((
+                                index,
+                                name.to_string(),
+                                metric.format_in(value, unit, &self.locale),
+                            ));
+                            continue;
+                        }
+                        let normalised = value_f64(field.value())
+                            .map(|v| device.normalise(field.name(), v, field.units()));
+                        match (field.name(), normalised) {
+                            ("start_position_lat", _) => {
+                                if let fitparser::Value::SInt32(lat) = field.value() {
+                                    entry.0 = Some(*lat);
+                                }
+                            }
+                            ("start_position_long", _) => {
+                                if let fitparser::Value::SInt32(long) = field.value() {
+                                    entry.1 = Some(*long);
+                                }
+                            }
+                            ("end_position_lat", _) => {
                                 if let fitparser::Value::SInt32(lat) = field.value() {
-                                    if let Some(old_lat) = summary.location.0 {
-                                        let avg_lat = old_lat / 2 + *lat / 2;
-                                        summary.location.0 = Some(avg_lat);
-                                    } else {
-                                        summary.location.0 = Some(*lat);
-                                    }
+                                    exit.0 = Some(*lat);
                                 }
                             }
-                            "start_position_long" | "end_position_long" => {
+                            ("end_position_long", _) => {
                                 if let fitparser::Value::SInt32(long) = field.value() {
-                                    if let Some(old_long) = summary.location.1 {
-                                        let avg_long = old_long / 2 + *long / 2;
-                                        summary.location.1 = Some(avg_long);
-                                    } else {
-                                        summary.location.1 = Some(*long);
-                                    }
+                                    exit.1 = Some(*long);
                                 }
                             }
-                            "total_elapsed_time" => {
+                            ("start_time", _) => {
+                                if let fitparser::Value::Timestamp(t) = field.value() {
+                                    summary.start_time = Some(*t);
+                                }
+                            }
+                            ("total_elapsed_time", _) => {
                                 if let fitparser::Value::Float64(t) = field.value() {
                                     summary.time = *t
                                 }
                             }
-                            "avg_temperature" => {
-                                if let fitparser::Value::SInt8(t) = field.value() {
-                                    summary.avg_temperature = Some(*t);
-                                    summary.set_unit(field.units())?;
+                            (_, Some(("avg_temperature", t, unit))) => {
+                                summary.avg_temperature = Some(t);
+                                summary.set_unit(unit)?;
+                            }
+                            ("avg_heart_rate", _) => {
+                                summary.avg_heart_rate = value_f64(field.value());
+                            }
+                            ("max_heart_rate", _) => {
+                                summary.max_heart_rate =
+                                    value_u32(field.value()).map(|hr| hr as u8);
+                            }
+                            (_, Some(("min_temperature", t, unit))) => {
+                                summary.min_temperature = Some(t.round() as i8);
+                                summary.set_unit(unit)?
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                fitparser::profile::field_types::MesgNum::Lap => {
+                    for field in record.fields() {
+                        if let ("start_time", Value::Timestamp(ts)) = (field.name(), field.value())
+                        {
+                            if bounds.contains(ts) {
+                                marks.push((*ts, self.locale.text("Lap")));
+                            }
+                        }
+                    }
+                }
+                fitparser::profile::field_types::MesgNum::Event => {
+                    let mut event_timestamp = None;
+                    let mut event = None;
+                    let mut dive_alert = None;
+                    let mut data = None;
+                    for field in record.fields() {
+                        match field.name() {
+                            "timestamp" => {
+                                if let Value::Timestamp(ts) = field.value() {
+                                    event_timestamp = Some(*ts);
                                 }
                             }
-                            "min_temperature" => {
-                                if let fitparser::Value::SInt8(t) = field.value() {
-                                    summary.min_temperature = Some(*t);
-                                    summary.set_unit(field.units())?
+                            "event" => event = value_string(field.value()),
+                            "dive_alert" => dive_alert = value_string(field.value()),
+                            "data" => data = value_u32(field.value()),
+                            _ => (),
+                        }
+                    }
+                    // the time on the setpoints is of the whole dive
+                    if let (Some(ts), Some(change)) = (
+                        event_timestamp,
+                        dive_alert.as_deref().and_then(LoopChange::from_dive_alert),
+                    ) {
+                        loop_changes.push((ts, change));
+                    }
+                    let Some(ts) = event_timestamp.filter(|ts| bounds.contains(ts)) else {
+                        continue;
+                    };
+                    if event.as_deref() == Some("user_marker") {
+                        marks.push((ts, self.locale.text("Bookmark")));
+                    }
+                    match dive_alert.as_deref() {
+                        Some("safety_stop_started") => stop_events.push((ts, true)),
+                        Some("safety_stop_complete" | "safety_stop_broken") => {
+                            stop_events.push((ts, false))
+                        }
+                        _ => (),
+                    }
+                    let Some((kind, text)) = event
+                        .as_deref()
+                        .and_then(|e| Event::from_message(e, dive_alert.as_deref()))
+                        .filter(|(kind, _)| self.events.contains(kind))
+                    else {
+                        continue;
+                    };
+                    // the start of a safety stop with the duration of the countdown
+                    let text = if dive_alert.as_deref() == Some("safety_stop_started") {
+                        let stop = self.safety_stop.unwrap_or_default();
+                        safety_stop::label(stop.duration, &self.locale)
+                    } else {
+                        self.locale.text(&text).to_string()
+                    };
+                    events.push((ts, kind, text, data));
+                }
+                fitparser::profile::field_types::MesgNum::Hr => {
+                    let Some(idx) = self.metrics.iter().position(|m| *m == Metric::HeartRate)
+                    else {
+                        continue;
+                    };
+                    let mut hr_timestamp = None;
+                    let mut fractional = 0f64;
+                    let mut event_timestamps = Vec::new();
+                    let mut bpms = Vec::new();
+                    for field in record.fields() {
+                        match field.name() {
+                            "timestamp" => {
+                                if let Value::Timestamp(ts) = field.value() {
+                                    hr_timestamp = Some(*ts);
                                 }
                             }
+                            "fractional_timestamp" => {
+                                fractional = value_f64(field.value()).unwrap_or(0.0)
+                            }
+                            "event_timestamp" => event_timestamps = values_f64(field.value()),
+                            "filtered_bpm" => bpms = values_f64(field.value()),
                             _ => (),
                         }
                     }
+                    if let (Some(ts), Some(event_timestamp)) =
+                        (hr_timestamp, event_timestamps.first())
+                    {
+                        let ts = ts + TimeDelta::microseconds((fractional * 1e6) as i64);
+                        hr_anchor = Some((ts, *event_timestamp));
+                    }
+                    let Some((anchor_time, anchor_event)) = hr_anchor else {
+                        continue;
+                    };
+                    for (event_timestamp, bpm) in event_timestamps.into_iter().zip(bpms) {
+                        let ts = anchor_time
+                            + TimeDelta::microseconds(
+                                ((event_timestamp - anchor_event) * 1e6) as i64,
+                            );
+                        if bounds.contains(&ts) {
+                            samples[idx].push((ts, bpm));
+                        }
+                    }
+                    if units[idx].is_empty() {
+                        units[idx] = Metric::HeartRate.default_unit().to_string();
+                    }
                 }
                 _ => {
+                    let mut raw_timestamp = None;
                     for field in record.fields() {
                         if field.name() == "timestamp" {
                             if let Value::Timestamp(ts) = field.value() {
-                                if before
-                                    && self.after_time_secs
-                                        >= ts.hour() * 60 * 60 + ts.minute() * 60 + ts.second()
-                                {
-                                    // TODO DEBUG print here
-                                    // println!("skip {}:{}:{}", ts.hour(), ts.minute(), ts.second());
-                                    continue;
-                                } else if self.before_time_secs > 0
-                                    && self.before_time_secs
-                                        < ts.hour() * 60 * 60 + ts.minute() * 60 + ts.second()
-                                {
-                                    // TODO DEBUG print here
-                                    // println!("skip record after {}:{}:{}", ts.hour(), ts.minute(), ts.second());
-                                    break;
-                                } else {
-                                    before = false;
+                                raw_timestamp = Some(*ts);
+                                if bounds.contains(ts) {
                                     timestamp = Some(*ts);
                                 }
                             }
-                        } else if field.name() == self.field {
-                            if let Value::Float64(v) = field.value() {
-                                has_depth = true;
-                                value = *v;
+                        } else if let Some((index, name, value, unit)) =
+                            developer_field(&descriptions, field)
+                        {
+                            if let Some(idx) = self
+                                .metrics
+                                .iter()
+                                .position(|m| m.is_developer_field(index, name))
+                            {
+                                values[idx] = Some(value);
+                                if units[idx].is_empty() {
+                                    units[idx] = unit.to_string();
+                                }
                             }
-                            if unit.is_empty() {
-                                unit = field.units().to_string();
+                        } else if let Some(value) = value_f64(field.value()) {
+                            let (name, value, unit) =
+                                device.normalise(field.name(), value, field.units());
+                            if let Some(idx) = self.metrics.iter().position(|m| {
+                                !matches!(m, Metric::Developer { .. }) && m.field() == Some(name)
+                            }) {
+                                values[idx] = Some(value);
+                                if units[idx].is_empty() {
+                                    units[idx] = unit.to_string();
+                                }
+                            }
+                            match name {
+                                "depth" => depth = Some(value),
+                                "temperature" => temperature = Some(value),
+                                "heading" => heading = Some(value),
+                                "po2" => ppo2 = Some(value),
+                                "pressure" => pressure = Some(value),
+                                _ => (),
                             }
                         }
                     }
-                    #[allow(clippy::unnecessary_unwrap)]
-                    if timestamp.is_some() && has_depth {
-                        if let Some(start_time) = start_time {
-                            let rounded_value = (value / self.tick).round() * self.tick;
-                            if (rounded_value - previous_value).abs() > self.tick {
-                                data.push_back((
-                                    timestamp.unwrap() - start_time,
-                                    format!("{rounded_value:.1}{unit}"),
-                                ));
-                                previous_value = rounded_value;
-                            }
-                        } else {
-                            if self.start_time_secs != 0 {
-                                let date =
-                                    unsafe { timestamp.as_ref().unwrap_unchecked().date_naive() };
-                                let time = NaiveTime::from_num_seconds_from_midnight_opt(
-                                    self.start_time_secs,
-                                    0,
-                                )
-                                .expect("Invalid start time!");
-                                let naive_datetime = date.and_time(time);
-                                let st = Local.from_local_datetime(&naive_datetime).unwrap();
-                                previous_time = unsafe { Some(timestamp.unwrap_unchecked() - st) };
-                                start_time = Some(st);
-                            } else {
-                                start_time = timestamp;
+                    if let Some(ts) = raw_timestamp {
+                        file_start = file_start.or(Some(ts));
+                        if descent.is_none() && depth.is_some_and(|d| d >= DESCENT_DEPTH) {
+                            descent = Some(ts);
+                        }
+                    }
+                    if let (Some(timestamp), Some(depth)) = (timestamp, depth) {
+                        profile.push((timestamp, depth, temperature));
+                    }
+                    if let (Some(timestamp), Some(heading)) = (timestamp, heading) {
+                        headings.push((timestamp, heading));
+                    }
+                    if let (Some(_), Some(pressure)) = (timestamp, pressure) {
+                        pressures.push(pressure);
+                    }
+                    if let Some(ts) = timestamp {
+                        window = Some(window.map_or((ts, ts), |(start, _)| (start, ts)));
+                    }
+                    // the range of the PPO2 of the records in the window
+                    if let (Some(_), Some(ppo2)) = (timestamp, ppo2) {
+                        summary.min_ppo2 = Some(summary.min_ppo2.map_or(ppo2, |p| p.min(ppo2)));
+                        summary.max_ppo2 = Some(summary.max_ppo2.map_or(ppo2, |p| p.max(ppo2)));
+                    }
+                    if let Some(timestamp) = timestamp {
+                        for (idx, value) in values.into_iter().enumerate() {
+                            if let Some(value) = value {
+                                samples[idx].push((timestamp, value));
                             }
-                            previous_value = value;
                         }
                     }
                 }
             }
         }
 
+        let entry = match entry {
+            (Some(lat), Some(long)) => Some((lat, long)),
+            _ => None,
+        };
+        let exit = match exit {
+            (Some(lat), Some(long)) => Some((lat, long)),
+            _ => None,
+        };
+        let positions: Vec<(i32, i32)> = entry.into_iter().chain(exit).collect();
+        summary.set_positions(&positions);
+        if !positions.is_empty() {
+            summary.waypoints.push(Waypoint {
+                entry: entry.map(semicircles_to_degrees),
+                exit: exit.map(semicircles_to_degrees),
+                start_time: summary.start_time,
+                duration: summary.time,
+                dive_number: summary.dive_number,
+                max_depth: summary.max_depth,
+                depth_unit: summary.depth_unit(),
+            });
+        }
+
+        // the heart beats of `hr` messages are merged with the records by time
+        for metric_samples in samples.iter_mut() {
+            metric_samples.sort_by_key(|(timestamp, _)| *timestamp);
+        }
+        headings.sort_by_key(|(timestamp, _)| *timestamp);
+        // the pressures of the transmitter in the window are over the ones of `tank_summary`
+        if let (Some(first), Some(last)) = (pressures.first(), pressures.last()) {
+            summary.start_pressure = Some(*first);
+            summary.end_pressure = Some(*last);
+        }
+
+        // the setpoints from the start of the file, if the loop of a rebreather ever changes
+        let mut setpoints = Vec::new();
+        if let (Some(start), false) = (file_start, loop_changes.is_empty()) {
+            loop_changes.sort_by_key(|(ts, _)| *ts);
+            setpoints = ccr::setpoints(start, &loop_changes, low_setpoint, high_setpoint);
+            // the time on the setpoints is of the records in the window like the PPO2
+            if let Some((window_start, window_end)) = window {
+                summary.setpoints = ccr::time_on_setpoints(&setpoints, window_start, window_end);
+            }
+        }
+
+        // the gas at the start of the dive is not a switch, the gases and the setpoints
+        // of `dive_settings` may follow the events
+        let dive_start = summary.start_time.or(file_start);
+        let events: Vec<(DateTime<Local>, String)> = events
+            .into_iter()
+            .filter(|(ts, kind, _, _)| {
+                *kind != Event::GasSwitch || dive_start.is_none_or(|start| *ts > start)
+            })
+            .map(|(ts, kind, text, data)| match kind {
+                Event::GasSwitch => match gas_indices.iter().find(|(i, _)| Some(*i) == data) {
+                    Some((_, (o2, he))) => (
+                        ts,
+                        format!("{text}: {}", self.locale.text(&gas_name(*o2, *he))),
+                    ),
+                    None => (ts, text),
+                },
+                Event::SetpointSwitch => {
+                    let setpoint = ccr::setpoint_at(&setpoints, &ts).unwrap_or(low_setpoint);
+                    (
+                        ts,
+                        format!("{text}: {}", self.locale.number(&format!("{setpoint:.2}"))),
+                    )
+                }
+                _ => (ts, text),
+            })
+            .collect();
+
+        // the times and the setpoints are shown at the records of the depth
+        for (metric, (metric_samples, unit)) in self
+            .metrics
+            .iter()
+            .zip(samples.iter_mut().zip(units.iter_mut()))
+        {
+            let since = |start: Option<DateTime<Local>>, ts: &DateTime<Local>| {
+                start.map_or(0.0, |start| (*ts - start).num_seconds().max(0) as f64)
+            };
+            *metric_samples = match metric {
+                Metric::DiveTime => profile
+                    .iter()
+                    .map(|(ts, _, _)| (*ts, since(descent, ts)))
+                    .collect(),
+                Metric::ElapsedTime => profile
+                    .iter()
+                    .map(|(ts, _, _)| (*ts, since(file_start, ts)))
+                    .collect(),
+                Metric::Clock => {
+                    *unit = self.clock_format.clone();
+                    profile
+                        .iter()
+                        .map(|(ts, _, _)| (*ts, time_of_day(ts).num_seconds() as f64))
+                        .collect()
+                }
+                Metric::Setpoint => profile
+                    .iter()
+                    .filter_map(|(ts, _, _)| Some((*ts, ccr::setpoint_at(&setpoints, ts)?)))
+                    .collect(),
+                _ => continue,
+            };
+        }
+
+        let mut data: VecDeque<Cue> = VecDeque::new();
+        let mut depths = Vec::new();
+        let mut temperatures = Vec::new();
+        let mut heading_track = Vec::new();
+        let mut chapters = Vec::new();
+        let mut previous_time = TimeDelta::zero();
+        let mut reference = None;
+        if let Some(first_timestamp) = samples.iter().filter_map(|s| s.first()).map(|s| s.0).min() {
+            let start_time = if !self.start_time.is_zero() {
+                let naive_datetime =
+                    first_timestamp.date_naive().and_time(NaiveTime::MIN) + self.start_time;
+                // the first of a time repeated when the clocks go back,
+                // a time skipped when the clocks go forward does not exist
+                Local
+                    .from_local_datetime(&naive_datetime)
+                    .earliest()
+                    .ok_or_else(|| {
+                        Fit2SrtError::StartTimeError(format!(
+                            "{naive_datetime} is not a local time"
+                        ))
+                    })?
+            } else {
+                first_timestamp
+            };
+            previous_time = first_timestamp - start_time;
+            reference = Some(start_time);
+
+            let metric_changes: Vec<(String, Vec<(TimeDelta, String)>)> = self
+                .metrics
+                .iter()
+                .zip(samples.iter().zip(units.iter()))
+                .map(|(metric, (metric_samples, unit))| {
+                    let mut metric_samples: Vec<(TimeDelta, f64)> = metric_samples
+                        .iter()
+                        .map(|(timestamp, value)| (*timestamp - start_time, *value))
+                        .collect();
+                    if *metric == Metric::Heading {
+                        metric_samples = heading::smooth(&metric_samples, self.heading_smoothing);
+                    }
+                    if let Some((_, filters)) = self.filters.iter().find(|(m, _)| m == metric) {
+                        metric_samples = filter::apply_all(filters, metric_samples);
+                    }
+                    let unit = if unit.is_empty() {
+                        metric.default_unit()
+                    } else {
+                        unit
+                    };
+                    let threshold = self
+                        .thresholds
+                        .iter()
+                        .find(|(m, _)| m == metric)
+                        .map_or(metric.threshold(), |(_, threshold)| *threshold);
+                    metric.changes(
+                        &metric_samples,
+                        unit,
+                        threshold,
+                        self.interpolation,
+                        &self.locale,
+                    )
+                })
+                .collect();
+
+            // a cue shows the value of each metric at the end of the cue,
+            // which is the baseline if the metric has not changed yet,
+            // the last cue lasts to the last sample
+            let last_time = samples
+                .iter()
+                .filter_map(|s| s.last())
+                .map(|s| s.0 - start_time)
+                .max()
+                .unwrap_or(previous_time);
+            let mut times: Vec<TimeDelta> = metric_changes
+                .iter()
+                .flat_map(|(_, changes)| changes.iter().map(|(time, _)| *time))
+                .chain([last_time])
+                .filter(|time| *time > previous_time)
+                .collect();
+            times.sort();
+            times.dedup();
+            let mut cue_start = previous_time;
+            for time in times {
+                let text = metric_changes
+                    .iter()
+                    .filter_map(|(baseline, changes)| {
+                        let idx = changes.partition_point(|(t, _)| *t <= time);
+                        idx.checked_sub(1)
+                            .map(|idx| &changes[idx].1)
+                            .or((!baseline.is_empty()).then_some(baseline))
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                // the last cue continues the value of the last change
+                match data.back_mut() {
+                    Some(last) if last.text == text => last.end = time,
+                    _ => data.push_back(Cue {
+                        start: cue_start,
+                        end: time,
+                        text,
+                    }),
+                }
+                cue_start = time;
+            }
+
+            // the filters of the depth apply to the profile of the safety stop,
+            // the thermocline and the overlay too
+            if let Some((_, filters)) = self.filters.iter().find(|(m, _)| *m == Metric::Depth) {
+                let filtered = filter::apply_all(
+                    filters,
+                    profile
+                        .iter()
+                        .map(|(timestamp, depth, _)| (*timestamp - start_time, *depth))
+                        .collect(),
+                );
+                // the filtered samples are the kept ones in order
+                let mut filtered = filtered.into_iter().peekable();
+                profile.retain_mut(|(timestamp, depth, _)| {
+                    match filtered.next_if(|(time, _)| *time == *timestamp - start_time) {
+                        Some((_, filtered_depth)) => {
+                            *depth = filtered_depth;
+                            true
+                        }
+                        None => false,
+                    }
+                });
+            }
+
+            let thermocline = self.thermocline.and_then(|min_gradient| {
+                let depth_temperatures: Vec<(f64, f64)> = profile
+                    .iter()
+                    .filter_map(|(_, d, t)| Some((*d, (*t)?)))
+                    .collect();
+                Thermocline::detect(&depth_temperatures, min_gradient)
+            });
+            if let Some(thermocline) = thermocline {
+                if let Some((timestamp, _, _)) =
+                    profile.iter().find(|(_, d, _)| *d >= thermocline.depth)
+                {
+                    insert_extra_cue(
+                        &mut data,
+                        *timestamp - start_time,
+                        format!(
+                            "{}: {}",
+                            self.locale.text("Thermocline"),
+                            self.locale.number(&thermocline.to_string())
+                        ),
+                    );
+                }
+                summary.thermocline = Some(thermocline);
+            }
+            depths = profile
+                .iter()
+                .map(|(timestamp, depth, _)| (*timestamp - start_time, *depth))
+                .collect();
+            temperatures = profile
+                .iter()
+                .filter_map(|(timestamp, _, t)| Some((*timestamp - start_time, (*t)?)))
+                .collect();
+            for (timestamp, text) in events {
+                insert_extra_cue(&mut data, timestamp - start_time, text);
+            }
+            let mut extra_cues: Vec<Cue> = Vec::new();
+            if let Some(stop) = self.safety_stop {
+                if stop_events.is_empty() {
+                    extra_cues.extend(stop.from_depths(&depths, &self.locale));
+                } else {
+                    let stop_events: Vec<(TimeDelta, bool)> = stop_events
+                        .iter()
+                        .map(|(timestamp, started)| (*timestamp - start_time, *started))
+                        .collect();
+                    extra_cues.extend(stop.from_events(&stop_events, &self.locale));
+                }
+            }
+            for cue in extra_cues {
+                let idx = data.partition_point(|c| c.start <= cue.start);
+                data.insert(idx, cue);
+            }
+            marks.sort_by_key(|(timestamp, _)| *timestamp);
+            chapters = marks
+                .into_iter()
+                .map(|(timestamp, title)| Chapter {
+                    start: timestamp - start_time,
+                    title: title.to_string(),
+                })
+                .collect();
+            let relative_headings: Vec<(TimeDelta, f64)> = headings
+                .iter()
+                .map(|(timestamp, heading)| (*timestamp - start_time, *heading))
+                .collect();
+            heading_track = heading::smooth(&relative_headings, self.heading_smoothing);
+            if let Some(min_angle) = self.turnaround {
+                for start in heading::turns(&heading_track, min_angle, TimeDelta::seconds(30)) {
+                    insert_extra_cue(
+                        &mut data,
+                        start,
+                        self.locale.text("Turn around").to_string(),
+                    );
+                }
+            }
+        }
+
         Ok(SrtIter {
             summary,
             count: 0,
+            reference,
+            previous_time,
             data,
-            previous_time: previous_time.unwrap_or_default(),
-            previous_iter_previous_time: TimeDelta::default(),
+            profile: depths,
+            temperatures,
+            headings: heading_track,
+            chapters,
+            time_format: self.time_format,
+            locale: self.locale.clone(),
         })
     }
 
     pub fn open<P: AsRef<Path>>(
-        self,
+        &self,
         path: P,
     ) -> Result<SrtIter, Box<dyn std::error::Error + Sync + Send + 'static>> {
         let mut fp = File::open(path)?;
         self.open_file(&mut fp)
     }
 
+    /// Concatenate the FIT files into one subtitle of each video of the map, in the order of the
+    /// start times, the dives of a day are numbered and the surface intervals between them are filled,
+    /// the cues of a later file overlapped by an earlier file are trimmed,
+    /// so the cue times always go forward.
+    /// There is a subtitle of each video up to the last video of the map, at least one
     pub fn concat<P: AsRef<Path>>(
-        self,
-        previous_iter_count: usize,
-        previous_iter_timedelta: TimeDelta,
-        path: P,
-    ) -> Result<SrtIter, Box<dyn std::error::Error + Sync + Send + 'static>> {
-        let mut it = self.open(path)?;
-        it.count = previous_iter_count;
-        it.previous_iter_previous_time = previous_iter_timedelta;
-        Ok(it)
+        &self,
+        paths: &[P],
+        map: &VideoMap,
+    ) -> Result<Vec<SrtIter>, Box<dyn std::error::Error + Sync + Send + 'static>> {
+        self.concat_records(&read_files(paths)?, map)
+    }
+
+    /// The subtitles of `concat` in each locale, the FIT files are only read once
+    pub fn concat_locales<P: AsRef<Path>>(
+        &self,
+        paths: &[P],
+        map: &VideoMap,
+        locales: &[Locale],
+    ) -> Result<Vec<Vec<SrtIter>>, Box<dyn std::error::Error + Sync + Send + 'static>> {
+        let files = read_files(paths)?;
+        let mut generator = self.clone();
+        locales
+            .iter()
+            .map(|locale| {
+                generator.locale(locale.clone());
+                generator.concat_records(&files, map)
+            })
+            .collect()
+    }
+
+    fn concat_records(
+        &self,
+        files: &[Vec<FitDataRecord>],
+        map: &VideoMap,
+    ) -> Result<Vec<SrtIter>, Box<dyn std::error::Error + Sync + Send + 'static>> {
+        let mut iters = Vec::with_capacity(files.len());
+        for (idx, records) in files.iter().enumerate() {
+            iters.push((
+                map.placements.get(idx).copied().flatten(),
+                self.parse_records(records)?,
+            ));
+        }
+        iters.sort_by_key(|(_, iter)| iter.summary.start_time);
+        number_dives(iters.iter_mut().map(|(_, iter)| &mut iter.summary));
+        let per_file = self.summary.as_ref().filter(|cue| cue.per_file);
+        let mut videos: Vec<Option<SrtIter>> = Vec::new();
+        videos.resize_with(map.videos(), || None);
+        // a file without a placement follows the previous file on its video
+        let mut video = 0;
+        for (placement, mut next_iter) in iters {
+            if let Some(cue) = per_file {
+                self.name_site(&mut next_iter.summary);
+                next_iter.add_summary(cue);
+            }
+            if let Some(placement) = placement {
+                video = placement.video;
+                next_iter.shift(placement.offset);
+            }
+            videos[video] = Some(match videos[video].take() {
+                Some(iter) if placement.is_some() => iter.append(next_iter, TimeDelta::zero())?,
+                Some(iter) => iter.concat(next_iter, map.gap)?,
+                None => next_iter,
+            });
+        }
+        Ok(videos
+            .into_iter()
+            .map(|iter| {
+                let mut iter = iter.unwrap_or_else(|| SrtIter {
+                    summary: Summary::default(),
+                    count: 0,
+                    reference: None,
+                    previous_time: TimeDelta::zero(),
+                    data: VecDeque::new(),
+                    profile: Vec::new(),
+                    temperatures: Vec::new(),
+                    headings: Vec::new(),
+                    chapters: Vec::new(),
+                    time_format: self.time_format,
+                    locale: self.locale.clone(),
+                });
+                if per_file.is_some() {
+                    self.name_site(&mut iter.summary);
+                } else {
+                    self.finish(&mut iter);
+                }
+                iter
+            })
+            .collect())
     }
 }
 
+/// The place of a FIT file on the videos
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    /// The index of the video
+    pub video: usize,
+    /// The cues of the file are moved by the offset on the video,
+    /// ex: the dive starts 00:02:10 into the second part of a split recording
+    pub offset: TimeDelta,
+}
+
+/// How the FIT files map onto one or more videos
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VideoMap {
+    /// The time between two files following each other on a video
+    pub gap: Gap,
+    // The placement of each file by the index of its path,
+    // a file without one follows the previous file on the same video
+    placements: Vec<Option<Placement>>,
+}
+
+impl VideoMap {
+    /// All the files on one video, with the gap between them
+    pub fn new(gap: Gap) -> Self {
+        Self {
+            gap,
+            placements: Vec::new(),
+        }
+    }
+
+    /// Place the file of the index in the paths on the video at the offset
+    pub fn place(&mut self, file: usize, video: usize, offset: TimeDelta) -> &mut Self {
+        if self.placements.len() <= file {
+            self.placements.resize(file + 1, None);
+        }
+        self.placements[file] = Some(Placement { video, offset });
+        self
+    }
+
+    /// The number of videos
+    pub fn videos(&self) -> usize {
+        self.placements
+            .iter()
+            .flatten()
+            .map(|placement| placement.video + 1)
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+/// How the time between two FIT files is placed on the video
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Gap {
+    /// The video kept recording between the files, so the real time between them is kept
+    #[default]
+    Keep,
+    /// The video was paused between the files, so the later file continues right after the earlier one
+    Remove,
+    /// The later file starts at a fixed time after the earlier one
+    Fixed(TimeDelta),
+}
+
+impl FromStr for Gap {
+    type Err = Fit2SrtError;
+
+    /// Parse `keep`, `remove` or the seconds of a fixed gap, up to a day
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "keep" => Ok(Self::Keep),
+            "remove" => Ok(Self::Remove),
+            secs => match secs.parse::<f64>() {
+                Ok(secs) if (0.0..=86400.0).contains(&secs) => Ok(Self::Fixed(
+                    TimeDelta::milliseconds((secs * 1000.0).round() as i64),
+                )),
+                _ => Err(Fit2SrtError::GapError(s.to_string())),
+            },
+        }
+    }
+}
+
+/// A clip of a video recording, which starts at the time of day and lasts the duration
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Clip {
+    pub start: TimeDelta,
+    pub duration: TimeDelta,
+}
+
+/// A subtitle cue, the times are from the beginning of the video
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cue {
+    pub start: TimeDelta,
+    pub end: TimeDelta,
+    pub text: String,
+}
+
 pub struct SrtIter {
     pub summary: Summary,
     pub count: usize,
+    // The time at the beginning of the video
+    reference: Option<DateTime<Local>>,
     previous_time: TimeDelta,
-    data: VecDeque<(TimeDelta, String)>,
-    previous_iter_previous_time: TimeDelta,
+    data: VecDeque<Cue>,
+    // The depths of the records
+    profile: Vec<(TimeDelta, f64)>,
+    temperatures: Vec<(TimeDelta, f64)>,
+    // The smoothed headings, continuous over the north
+    headings: Vec<(TimeDelta, f64)>,
+    // The laps and the bookmarks, numbered when they are read
+    chapters: Vec<Chapter>,
+    time_format: TimeFormat,
+    locale: Locale,
 }
 
 impl SrtIter {
     pub fn previous_time(&self) -> TimeDelta {
         self.previous_time
     }
+
+    /// The time at the beginning of the video
+    pub fn reference(&self) -> Option<DateTime<Local>> {
+        self.reference
+    }
+
+    /// The depths of the dive, the times are from the beginning of the video
+    pub fn profile(&self) -> &[(TimeDelta, f64)] {
+        &self.profile
+    }
+
+    /// The water temperatures of the dive, the times are from the beginning of the video
+    pub fn temperatures(&self) -> &[(TimeDelta, f64)] {
+        &self.temperatures
+    }
+
+    /// The smoothed headings in degrees, the times are from the beginning of the video,
+    /// the values are continuous over the north, ex: 359, 361
+    pub fn headings(&self) -> &[(TimeDelta, f64)] {
+        &self.headings
+    }
+
+    /// The laps and the bookmarks of the diver numbered by their kind, ex: Lap 1, Bookmark 1, Lap 2,
+    /// the chapters before the beginning of the video are skipped
+    pub fn chapters(&self) -> Vec<Chapter> {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        self.chapters
+            .iter()
+            .filter(|chapter| chapter.start >= TimeDelta::zero())
+            .map(|chapter| {
+                let n = match counts.iter_mut().find(|(title, _)| *title == chapter.title) {
+                    Some((_, n)) => {
+                        *n += 1;
+                        *n
+                    }
+                    None => {
+                        counts.push((&chapter.title, 1));
+                        1
+                    }
+                };
+                Chapter {
+                    start: chapter.start,
+                    title: format!("{} {n}", chapter.title),
+                }
+            })
+            .collect()
+    }
+
+    /// The end of the last cue or sample from the beginning of the video
+    pub fn duration(&self) -> TimeDelta {
+        let cue_end = self.data.iter().map(|cue| cue.end).max();
+        let sample_end = self.profile.last().map(|(t, _)| *t);
+        cue_end.max(sample_end).unwrap_or(self.previous_time)
+    }
+
+    /// Add the summary cue after the delay from the start of the first cue and/or the end of the last cue
+    pub fn add_summary(&mut self, cue: &SummaryCue) {
+        if self.summary.is_empty() {
+            return;
+        }
+        let template = cue
+            .template
+            .as_deref()
+            .unwrap_or(default_template(self.locale.lang));
+        let text = self.summary.render_in(template, &self.locale);
+        let mut starts = Vec::new();
+        if let (SummaryPosition::Start | SummaryPosition::Both, Some(first)) =
+            (cue.position, self.data.front())
+        {
+            starts.push(first.start.checked_add(&cue.delay));
+        }
+        if let (SummaryPosition::End | SummaryPosition::Both, Some(end)) =
+            (cue.position, self.data.iter().map(|cue| cue.end).max())
+        {
+            starts.push(end.checked_add(&cue.delay));
+        }
+        // a cue out of the range of the times is not added
+        for start in starts.into_iter().flatten() {
+            let Some(end) = start.checked_add(&cue.duration) else {
+                continue;
+            };
+            let idx = self.data.partition_point(|c| c.start <= start);
+            self.data.insert(
+                idx,
+                Cue {
+                    start,
+                    end,
+                    text: text.clone(),
+                },
+            );
+        }
+    }
+
+    /// Merge the cues of an existing subtitle, ex: the narration of the video,
+    /// the cues are renumbered when they are written
+    pub fn merge_subtitle(&mut self, cues: &[Cue], overlap: Overlap) {
+        let generated: Vec<Cue> = self.data.drain(..).collect();
+        self.data = subtitle::merge(&generated, cues, overlap).into();
+    }
+
+    /// Append the cues of a later FIT file after the gap
+    pub fn concat(self, other: SrtIter, gap: Gap) -> Result<SrtIter, Fit2SrtError> {
+        let (Some(reference), Some(other_reference)) = (self.reference, other.reference) else {
+            return self.append(other, TimeDelta::zero());
+        };
+        let end = self.end();
+        let offset = match gap {
+            Gap::Keep => other_reference - reference,
+            Gap::Remove => end - other.previous_time,
+            Gap::Fixed(gap) => end
+                .checked_add(&gap)
+                .and_then(|start| start.checked_sub(&other.previous_time))
+                .ok_or_else(|| Fit2SrtError::GapError(format!("{} seconds", gap.num_seconds())))?,
+        };
+        self.append(other, offset)
+    }
+
+    /// Append the cues of a later FIT file moved by the offset,
+    /// the cues before the end of this file are trimmed
+    pub fn append(mut self, other: SrtIter, offset: TimeDelta) -> Result<SrtIter, Fit2SrtError> {
+        let summary = std::mem::take(&mut self.summary).merge(&other.summary)?;
+        if other.reference.is_none() || self.reference.is_none() {
+            let mut iter = if self.reference.is_some() {
+                self
+            } else {
+                other
+            };
+            iter.summary = summary;
+            return Ok(iter);
+        }
+        let end = self.end();
+        for mut cue in other.data {
+            cue.start += offset;
+            cue.end += offset;
+            if cue.end <= end {
+                continue;
+            }
+            if cue.start < end {
+                cue.start = end;
+            }
+            self.data.push_back(cue);
+        }
+        append_track(&mut self.profile, other.profile, offset);
+        append_track(&mut self.temperatures, other.temperatures, offset);
+        append_track(&mut self.headings, other.headings, offset);
+        for mut chapter in other.chapters {
+            chapter.start += offset;
+            self.chapters.push(chapter);
+        }
+        self.summary = summary;
+        Ok(self)
+    }
+
+    /// Move the cues and the samples later on the video by the offset
+    pub fn shift(&mut self, offset: TimeDelta) {
+        for cue in self.data.iter_mut() {
+            cue.start += offset;
+            cue.end += offset;
+        }
+        for track in [
+            &mut self.profile,
+            &mut self.temperatures,
+            &mut self.headings,
+        ] {
+            for (time, _) in track.iter_mut() {
+                *time += offset;
+            }
+        }
+        for chapter in self.chapters.iter_mut() {
+            chapter.start += offset;
+        }
+        self.previous_time += offset;
+        // the time at the beginning of the video
+        self.reference = self.reference.map(|reference| reference - offset);
+    }
+
+    // The end of the last cue
+    fn end(&self) -> TimeDelta {
+        self.data.back().map_or(self.previous_time, |cue| cue.end)
+    }
+
+    /// Split the cues onto the clips of a video recording,
+    /// the cue times of each part are from the beginning of its clip
+    pub fn split(self, clips: &[Clip]) -> Result<Vec<SrtIter>, Fit2SrtError> {
+        clips
+            .iter()
+            .map(|clip| {
+                let mut data = VecDeque::new();
+                let mut profile = Vec::new();
+                let mut temperatures = Vec::new();
+                let mut headings = Vec::new();
+                let mut chapters = Vec::new();
+                let mut clip_reference = None;
+                if let Some(reference) = self.reference {
+                    let start_time = clip_start(&reference, clip.start)?;
+                    let offset = start_time - reference;
+                    for cue in self.data.iter() {
+                        let start = (cue.start - offset).max(TimeDelta::zero());
+                        let end = (cue.end - offset).min(clip.duration);
+                        if start < end {
+                            data.push_back(Cue {
+                                start,
+                                end,
+                                text: cue.text.clone(),
+                            });
+                        }
+                    }
+                    profile = clip_track(&self.profile, offset, clip.duration);
+                    temperatures = clip_track(&self.temperatures, offset, clip.duration);
+                    headings = clip_track(&self.headings, offset, clip.duration);
+                    chapters = self
+                        .chapters
+                        .iter()
+                        .map(|chapter| Chapter {
+                            start: chapter.start - offset,
+                            title: chapter.title.clone(),
+                        })
+                        .filter(|chapter| chapter.start <= clip.duration)
+                        .collect();
+                    clip_reference = Some(start_time);
+                }
+                Ok(SrtIter {
+                    summary: self.summary.clone(),
+                    count: 0,
+                    reference: clip_reference,
+                    previous_time: data.front().map_or(TimeDelta::zero(), |cue| cue.start),
+                    data,
+                    profile,
+                    temperatures,
+                    headings,
+                    chapters,
+                    time_format: self.time_format,
+                    locale: self.locale.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The local time of the time of day nearest to the reference, on the day before or after it
+/// if the time of day is more than 12 hours away
+fn nearest_time(reference: &DateTime<Local>, time: TimeDelta) -> NaiveDateTime {
+    let day = reference.date_naive();
+    let since_reference = time - time_of_day(reference);
+    let day = if since_reference > TimeDelta::hours(12) {
+        day.pred_opt().unwrap_or(day)
+    } else if since_reference < -TimeDelta::hours(12) {
+        day.succ_opt().unwrap_or(day)
+    } else {
+        day
+    };
+    day.and_time(NaiveTime::MIN) + time
+}
+
+/// The local times of `after` and `before` of a file, without a bound if it is not given
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+struct Window {
+    after: Option<NaiveDateTime>,
+    before: Option<NaiveDateTime>,
+}
+
+impl Window {
+    fn contains(&self, ts: &DateTime<Local>) -> bool {
+        let time = ts.naive_local();
+        self.after.is_none_or(|after| after < time)
+            && self.before.is_none_or(|before| before >= time)
+    }
+}
+
+/// The time of the clip start on the day of the reference, or on the day before or after
+/// if it is nearer, ex: a clip at 00:05 of a dive from 23:50
+fn clip_start(
+    reference: &DateTime<Local>,
+    start: TimeDelta,
+) -> Result<DateTime<Local>, Fit2SrtError> {
+    let naive_datetime = nearest_time(reference, start);
+    // the first of a time repeated when the clocks go back,
+    // a time skipped when the clocks go forward does not exist
+    Local
+        .from_local_datetime(&naive_datetime)
+        .earliest()
+        .ok_or_else(|| Fit2SrtError::ClipError(format!("{naive_datetime} is not a local time")))
+}
+
+/// Append the samples after the end of the track
+fn append_track(
+    track: &mut Vec<(TimeDelta, f64)>,
+    other: Vec<(TimeDelta, f64)>,
+    offset: TimeDelta,
+) {
+    let end = track.last().map(|(t, _)| *t);
+    for (time, value) in other {
+        let time = time + offset;
+        if end.is_none_or(|end| time > end) {
+            track.push((time, value));
+        }
+    }
+}
+
+/// The samples in a clip, the times are from the beginning of the clip
+fn clip_track(
+    track: &[(TimeDelta, f64)],
+    offset: TimeDelta,
+    duration: TimeDelta,
+) -> Vec<(TimeDelta, f64)> {
+    track
+        .iter()
+        .map(|(time, value)| (*time - offset, *value))
+        .filter(|(time, _)| *time >= TimeDelta::zero() && *time <= duration)
+        .collect()
+}
+
+/// Insert a cue of `EXTRA_CUE_DURATION` after the cues starting before or at the same time
+fn insert_extra_cue(data: &mut VecDeque<Cue>, start: TimeDelta, text: String) {
+    let idx = data.partition_point(|c| c.start <= start);
+    data.insert(
+        idx,
+        Cue {
+            start,
+            end: start + EXTRA_CUE_DURATION,
+            text,
+        },
+    );
 }
 
 impl std::iter::Iterator for SrtIter {
     type Item = (usize, TimeDelta, SrtString);
     fn next(&mut self) -> Option<Self::Item> {
+        let cue = self.data.pop_front()?;
         self.count += 1;
-        let time = self.previous_time + self.previous_iter_previous_time;
-        let previous_time_str = delta_srt_format(&time);
-        self.data.pop_front().map(|i| {
-            self.previous_time = i.0;
-            (
+        self.previous_time = cue.end;
+        let time = self.time_format.snap(&cue.start);
+        Some((
+            self.count,
+            time,
+            format!(
+                "{}\n{} --> {}\n{}",
                 self.count,
-                time,
-                format!(
-                    "{}\n{} --> {}\n{}",
-                    self.count,
-                    previous_time_str,
-                    delta_srt_format(&i.0),
-                    i.1
-                ),
-            )
-        })
+                self.time_format.format(&time),
+                self.time_format.format(&cue.end),
+                cue.text
+            ),
+        ))
+    }
+}
+
+/// The time of day of a timestamp, the window parameters of `SrtGenerator` compare against it
+fn time_of_day(ts: &DateTime<Local>) -> TimeDelta {
+    TimeDelta::seconds(ts.num_seconds_from_midnight() as i64)
+        + TimeDelta::nanoseconds(ts.nanosecond() as i64)
+}
+
+/// The records of the FIT files
+fn read_files<P: AsRef<Path>>(
+    paths: &[P],
+) -> Result<Vec<Vec<FitDataRecord>>, Box<dyn std::error::Error + Sync + Send + 'static>> {
+    paths
+        .iter()
+        .map(|path| Ok(fitparser::from_reader(&mut File::open(path)?)?))
+        .collect()
+}
+
+fn value_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Float64(v) => Some(*v),
+        Value::Float32(v) => Some(*v as f64),
+        Value::SInt8(v) => Some(*v as f64),
+        Value::SInt16(v) => Some(*v as f64),
+        Value::SInt32(v) => Some(*v as f64),
+        value => value_u32(value).map(|v| v as f64),
+    }
+}
+
+/// A developer field described by a `field_description` message
+struct FieldDescription {
+    index: u8,
+    number: u8,
+    name: String,
+}
+
+/// The developer data index, the name, the value and the unit of a developer field,
+/// the field is named and scaled by its `field_description` message when decoded,
+/// so the index is the one of the description with the same name and number
+fn developer_field<'a>(
+    descriptions: &[FieldDescription],
+    field: &'a fitparser::FitDataField,
+) -> Option<(u8, &'a str, f64, &'a str)> {
+    let description = descriptions
+        .iter()
+        .find(|d| d.name == field.name() && d.number == field.number())?;
+    let value = value_f64(field.value())?;
+    Some((description.index, field.name(), value, field.units()))
+}
+
+/// The values of an array field, or the value of a single value field
+fn values_f64(value: &Value) -> Vec<f64> {
+    match value {
+        Value::Array(values) => values.iter().filter_map(value_f64).collect(),
+        value => value_f64(value).into_iter().collect(),
+    }
+}
+
+fn value_u32(value: &Value) -> Option<u32> {
+    match value {
+        Value::UInt8(n) | Value::UInt8z(n) | Value::Byte(n) | Value::Enum(n) => Some(*n as u32),
+        Value::UInt16(n) | Value::UInt16z(n) => Some(*n as u32),
+        Value::UInt32(n) | Value::UInt32z(n) => Some(*n),
+        _ => None,
+    }
+}
+
+fn value_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        value => value_u32(value).map(|n| n.to_string()),
     }
 }
 
@@ -309,3 +1733,149 @@ fn parse_garmin_g1() -> Result<(), Box<dyn std::error::Error + Sync + Send + 'st
     assert_eq!(srt, "4\n00:00:03,000 --> 00:00:06,000\n1.8m".to_string());
     Ok(())
 }
+
+#[test]
+fn concat_overlapped() -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static>> {
+    let iter = SrtGenerator::default().open("../assets/713-2.fit")?;
+    let cue_number = iter.count();
+    let mut iters = SrtGenerator::default().concat(
+        &["../assets/713-2.fit", "../assets/713-2.fit"],
+        &VideoMap::default(),
+    )?;
+    assert_eq!(iters.len(), 1);
+    assert_eq!(iters.remove(0).count(), cue_number);
+    Ok(())
+}
+
+#[test]
+fn concat_onto_videos() -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static>> {
+    let mut generator = SrtGenerator::default();
+    generator.summary(None);
+    let cues = |iter: &SrtIter| -> Vec<(TimeDelta, TimeDelta)> {
+        iter.data.iter().map(|cue| (cue.start, cue.end)).collect()
+    };
+    let single = generator.open("../assets/garmin_g1.fit")?;
+    let paths = ["../assets/garmin_g1.fit", "../assets/garmin_g1.fit"];
+
+    // a split recording, the second video starts 10 seconds before the dive
+    let mut map = VideoMap::default();
+    map.place(1, 1, TimeDelta::seconds(10));
+    let iters = generator.concat(&paths, &map)?;
+    assert_eq!(iters.len(), 2);
+    assert_eq!(cues(&iters[0]), cues(&single));
+    let moved: Vec<(TimeDelta, TimeDelta)> = cues(&single)
+        .into_iter()
+        .map(|(start, end)| (start + TimeDelta::seconds(10), end + TimeDelta::seconds(10)))
+        .collect();
+    assert_eq!(cues(&iters[1]), moved);
+
+    // the second file overlaps the first one on a video, the times only go forward
+    let mut map = VideoMap::default();
+    map.place(1, 0, TimeDelta::seconds(2));
+    let iters = generator.concat(&paths, &map)?;
+    assert_eq!(iters.len(), 1);
+    let times = cues(&iters[0]);
+    assert!(times.len() > cues(&single).len());
+    assert!(times.iter().all(|(start, end)| start < end));
+    assert!(times.windows(2).all(|w| w[0].1 <= w[1].0));
+    Ok(())
+}
+
+#[test]
+fn concat_in_locales() -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static>> {
+    let paths = ["../assets/garmin_g1.fit"];
+    let locales = [Locale::default(), Locale::new(crate::locale::Lang::Ja)];
+    let tracks = SrtGenerator::default().concat_locales(&paths, &VideoMap::default(), &locales)?;
+    assert_eq!(tracks.len(), 2);
+    for (mut iters, locale) in tracks.into_iter().zip(locales) {
+        let mut generator = SrtGenerator::default();
+        generator.locale(locale);
+        let expected: Vec<String> = generator
+            .concat(&paths, &VideoMap::default())?
+            .remove(0)
+            .map(|(_, _, srt)| srt)
+            .collect();
+        let srts: Vec<String> = iters.remove(0).map(|(_, _, srt)| srt).collect();
+        assert_eq!(srts, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn window_across_midnight() {
+    let first = Local.with_ymd_and_hms(2024, 9, 7, 23, 40, 0).unwrap();
+    let at = |m: i64| first + TimeDelta::minutes(m);
+    let mut generator = SrtGenerator::default();
+    generator.after(TimeDelta::hours(23) + TimeDelta::minutes(50));
+    let window = generator.window(&first);
+    assert!(!window.contains(&at(5)));
+    // after 00:00 of the next day
+    assert!(window.contains(&at(30)));
+    generator.before(TimeDelta::minutes(20));
+    let window = generator.window(&first);
+    assert!(window.contains(&at(30)));
+    assert!(!window.contains(&at(50)));
+    // the times of day nearest to the file are of the day before
+    let mut generator = SrtGenerator::default();
+    generator.after(TimeDelta::hours(23));
+    generator.before(TimeDelta::hours(1));
+    let window = generator.window(&(first + TimeDelta::hours(1)));
+    assert!(window.contains(&at(60)));
+    assert!(!window.contains(&at(90)));
+}
+
+#[test]
+fn parse_gap() {
+    assert_eq!("Remove".parse::<Gap>().unwrap(), Gap::Remove);
+    assert_eq!(
+        "1.5".parse::<Gap>().unwrap(),
+        Gap::Fixed(TimeDelta::milliseconds(1500))
+    );
+    for gap in ["-1", "1e300", "inf", "NaN", "later"] {
+        assert!(gap.parse::<Gap>().is_err());
+    }
+}
+
+#[test]
+fn split_clips() -> Result<(), Box<dyn std::error::Error + Sync + Send + 'static>> {
+    let cue = |start: i64, end: i64, text: &str| Cue {
+        start: TimeDelta::seconds(start),
+        end: TimeDelta::seconds(end),
+        text: text.to_string(),
+    };
+    // a dive from 23:59:58 to 00:00:06 of the next day
+    let reference = Local
+        .with_ymd_and_hms(2024, 1, 10, 23, 59, 58)
+        .single()
+        .ok_or("no local time")?;
+    let iter = SrtIter {
+        summary: Summary::default(),
+        count: 0,
+        reference: Some(reference),
+        previous_time: TimeDelta::zero(),
+        data: [cue(0, 3, "1.5m"), cue(3, 8, "2.0m")].into(),
+        profile: vec![(TimeDelta::zero(), 1.5), (TimeDelta::seconds(3), 2.0)],
+        temperatures: Vec::new(),
+        headings: Vec::new(),
+        chapters: Vec::new(),
+        time_format: TimeFormat::default(),
+        locale: Locale::default(),
+    };
+    let clips = [
+        Clip {
+            start: TimeDelta::hours(23) + TimeDelta::minutes(59) + TimeDelta::seconds(57),
+            duration: TimeDelta::seconds(2),
+        },
+        Clip {
+            start: TimeDelta::seconds(1),
+            duration: TimeDelta::seconds(60),
+        },
+    ];
+    let parts = iter.split(&clips)?;
+    assert_eq!(parts[0].data, [cue(1, 2, "1.5m")]);
+    assert_eq!(parts[0].profile, [(TimeDelta::seconds(1), 1.5)]);
+    // the clip after midnight is on the next day
+    assert_eq!(parts[1].data, [cue(0, 5, "2.0m")]);
+    assert_eq!(parts[1].profile, [(TimeDelta::zero(), 2.0)]);
+    Ok(())
+}
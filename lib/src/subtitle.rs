@@ -0,0 +1,220 @@
+use chrono::TimeDelta;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::error::Fit2SrtError;
+use crate::srt_iter::Cue;
+
+/// How the cues of the dive data are shown with the cues of an existing subtitle
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Overlap {
+    /// The lines of the dive data are stacked over the lines of the subtitle in one cue
+    #[default]
+    Stack,
+    /// The cues of the dive data are moved to the top of the video with `{\an8}`
+    Position,
+}
+
+impl FromStr for Overlap {
+    type Err = Fit2SrtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stack" => Ok(Self::Stack),
+            "position" | "top" => Ok(Self::Position),
+            _ => Err(Fit2SrtError::SubtitleError(format!(
+                "unsupported overlap: {s}"
+            ))),
+        }
+    }
+}
+
+/// Read the cues of a SRT or WebVTT file
+pub fn open<P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<Cue>, Box<dyn std::error::Error + Sync + Send + 'static>> {
+    Ok(parse(&std::fs::read_to_string(path)?)?)
+}
+
+/// Parse the cues of SRT or WebVTT, the numbers, the identifiers and the settings of cues
+/// are dropped, the `WEBVTT` header and the `NOTE`, `STYLE` and `REGION` blocks are skipped
+pub fn parse(content: &str) -> Result<Vec<Cue>, Fit2SrtError> {
+    let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut cues = Vec::new();
+    for block in content.split("\n\n") {
+        let lines: Vec<&str> = block.lines().skip_while(|l| l.trim().is_empty()).collect();
+        let Some(first) = lines.first() else {
+            continue;
+        };
+        if ["WEBVTT", "NOTE", "STYLE", "REGION"]
+            .iter()
+            .any(|keyword| first.starts_with(keyword))
+        {
+            continue;
+        }
+        let Some(timing) = lines.iter().position(|l| l.contains("-->")) else {
+            return Err(Fit2SrtError::SubtitleError(format!(
+                "missing the times of the cue: {first}"
+            )));
+        };
+        let mut tokens = lines[timing].split_whitespace();
+        let (Some(start), Some("-->"), Some(end)) = (tokens.next(), tokens.next(), tokens.next())
+        else {
+            return Err(Fit2SrtError::SubtitleError(format!(
+                "invalid times: {}",
+                lines[timing]
+            )));
+        };
+        cues.push(Cue {
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+            text: lines[timing + 1..].join("\n"),
+        });
+    }
+    Ok(cues)
+}
+
+/// `01:02:03,004`, `01:02:03.004` or `02:03.004`
+fn parse_time(s: &str) -> Result<TimeDelta, Fit2SrtError> {
+    let err = || Fit2SrtError::SubtitleError(format!("invalid time: {s}"));
+    let (hms, ms) = s.split_once([',', '.']).ok_or_else(err)?;
+    let mut parts = hms
+        .split(':')
+        .map(|p| p.parse::<i64>().map_err(|_| err()))
+        .collect::<Result<Vec<i64>, _>>()?;
+    if parts.len() == 2 {
+        parts.insert(0, 0);
+    }
+    // the fraction of a second, ex: `,5` is 500 milliseconds
+    if ms.is_empty() || !ms.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(err());
+    }
+    let digits = &ms[..ms.len().min(3)];
+    let (&[h, m, s], Ok(ms)) = (&parts[..], digits.parse::<i64>()) else {
+        return Err(err());
+    };
+    let ms = ms * 10i64.pow(3 - digits.len() as u32);
+    if m >= 60 || s >= 60 {
+        return Err(err());
+    }
+    Ok(TimeDelta::milliseconds(((h * 60 + m) * 60 + s) * 1000 + ms))
+}
+
+/// Merge the cues of the dive data with the cues of an existing subtitle, ordered by the start
+pub fn merge(generated: &[Cue], existing: &[Cue], overlap: Overlap) -> Vec<Cue> {
+    let mut cues = match overlap {
+        Overlap::Stack => stack(generated, existing),
+        Overlap::Position => existing
+            .iter()
+            .cloned()
+            .chain(generated.iter().map(|cue| Cue {
+                text: format!("{{\\an8}}{}", cue.text),
+                ..cue.clone()
+            }))
+            .collect(),
+    };
+    cues.sort_by_key(|cue| cue.start);
+    cues
+}
+
+/// Split the cues at each start and end, so the cues showing together become one cue,
+/// the lines of the dive data are over the lines of the subtitle
+fn stack(generated: &[Cue], existing: &[Cue]) -> Vec<Cue> {
+    let mut all: Vec<(bool, &Cue)> = generated
+        .iter()
+        .map(|cue| (false, cue))
+        .chain(existing.iter().map(|cue| (true, cue)))
+        .collect();
+    all.sort_by_key(|(_, cue)| cue.start);
+    let mut times: Vec<TimeDelta> = all
+        .iter()
+        .flat_map(|(_, cue)| [cue.start, cue.end])
+        .collect();
+    times.sort();
+    times.dedup();
+
+    let mut cues: Vec<Cue> = Vec::new();
+    let mut active: Vec<(bool, &Cue)> = Vec::new();
+    let mut next = 0;
+    for window in times.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        while next < all.len() && all[next].1.start <= start {
+            active.push(all[next]);
+            next += 1;
+        }
+        active.retain(|(_, cue)| cue.end > start);
+        if active.is_empty() {
+            continue;
+        }
+        let text = [false, true]
+            .iter()
+            .flat_map(|from_existing| {
+                active
+                    .iter()
+                    .filter(move |(existing, _)| existing == from_existing)
+                    .map(|(_, cue)| cue.text.as_str())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        match cues.last_mut() {
+            Some(last) if last.end == start && last.text == text => last.end = end,
+            _ => cues.push(Cue { start, end, text }),
+        }
+    }
+    cues
+}
+
+#[test]
+fn parse_and_merge() {
+    let ms = TimeDelta::milliseconds;
+    let srt = "\u{feff}1\r\n00:00:01,000 --> 00:00:04,000\r\nHello\r\nthere\r\n\r\n2\r\n00:00:05,500 --> 00:00:06,000\r\nBye\r\n";
+    let narration = parse(srt).unwrap();
+    assert_eq!(narration.len(), 2);
+    assert_eq!(narration[0].text, "Hello\nthere");
+    assert_eq!((narration[1].start, narration[1].end), (ms(5500), ms(6000)));
+
+    let vtt = "WEBVTT - dive\n\nNOTE a comment\n\nintro\n00:01.000 --> 00:04.000 line:0 align:start\nHello\nthere\n\n00:00:05.500 --> 00:00:06.000\nBye\n";
+    assert_eq!(parse(vtt).unwrap(), narration);
+    assert!(parse("1\n00:00:01,000 -> 00:00:02,000\nHello\n").is_err());
+    assert!(parse("1\n00:00:61,000 --> 00:01:02,000\nHello\n").is_err());
+    assert_eq!(parse_time("00:00:01,5").unwrap(), ms(1500));
+    assert_eq!(parse_time("00:01.25").unwrap(), ms(1250));
+    assert!(parse_time("00:00:01,-5").is_err());
+
+    let depths = [
+        Cue {
+            start: ms(0),
+            end: ms(2000),
+            text: "3.0m".to_string(),
+        },
+        Cue {
+            start: ms(2000),
+            end: ms(6000),
+            text: "4.2m".to_string(),
+        },
+    ];
+    let texts = |cues: Vec<Cue>| -> Vec<(i64, i64, String)> {
+        cues.into_iter()
+            .map(|c| (c.start.num_milliseconds(), c.end.num_milliseconds(), c.text))
+            .collect()
+    };
+    assert_eq!(
+        texts(merge(&depths, &narration, Overlap::Stack)),
+        vec![
+            (0, 1000, "3.0m".to_string()),
+            (1000, 2000, "3.0m\nHello\nthere".to_string()),
+            (2000, 4000, "4.2m\nHello\nthere".to_string()),
+            (4000, 5500, "4.2m".to_string()),
+            (5500, 6000, "4.2m\nBye".to_string()),
+        ]
+    );
+    assert_eq!(
+        texts(merge(&depths, &narration, Overlap::Position)),
+        vec![
+            (0, 2000, "{\\an8}3.0m".to_string()),
+            (1000, 4000, "Hello\nthere".to_string()),
+            (2000, 6000, "{\\an8}4.2m".to_string()),
+            (5500, 6000, "Bye".to_string()),
+        ]
+    );
+}
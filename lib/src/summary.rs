@@ -1,13 +1,81 @@
-#[derive(Copy, Clone, Default)]
+use chrono::{DateTime, Local, NaiveDate, TimeDelta};
+use std::str::FromStr;
+
+use crate::ccr::{add_time, setpoints_format};
+use crate::error::Fit2SrtError;
+use crate::locale::{Lang, Locale};
+use crate::metric::Metric;
+use crate::thermocline::Thermocline;
+use crate::waypoint::Waypoint;
+
+pub const DEFAULT_TEMPLATE: &str = "Summary:
+Date: {date} {time}[ (#{dive_number})]
+Dive of the day: {dive_of_day}
+Location: {location}
+Dive time: {dive_time}
+Depth: {avg_depth}[ (max: {max_depth})]
+Temperature: {avg_temperature}[ (min: {min_temperature})]
+Heart rate: {avg_heart_rate}[ (max: {max_heart_rate})]
+Thermocline: {thermocline}
+Gas: {gas}
+Tank: {start_pressure} → {end_pressure}
+Setpoints: {setpoints}[ (PPO2: {min_ppo2} - {max_ppo2})]
+Surface interval: {surface_interval}[ (residual N2: {residual_n2})]
+Device: {device}[ ({serial_number})]";
+
+const ZH_TEMPLATE: &str = "潛水摘要：
+日期：{date} {time}[ (#{dive_number})]
+當日第 {dive_of_day} 潛
+地點：{location}
+潛水時間：{dive_time}
+深度：{avg_depth}[ (最深：{max_depth})]
+水溫：{avg_temperature}[ (最低：{min_temperature})]
+心率：{avg_heart_rate}[ (最高：{max_heart_rate})]
+斜溫層：{thermocline}
+氣體：{gas}
+氣瓶：{start_pressure} → {end_pressure}
+設定點：{setpoints}[ (PPO2：{min_ppo2} - {max_ppo2})]
+水面休息：{surface_interval}[ (殘餘氮：{residual_n2})]
+裝置：{device}[ ({serial_number})]";
+
+const JA_TEMPLATE: &str = "ダイブサマリー：
+日付：{date} {time}[ (#{dive_number})]
+本日 {dive_of_day} 本目
+場所：{location}
+潜水時間：{dive_time}
+水深：{avg_depth}[ (最大：{max_depth})]
+水温：{avg_temperature}[ (最低：{min_temperature})]
+心拍数：{avg_heart_rate}[ (最大：{max_heart_rate})]
+水温躍層：{thermocline}
+ガス：{gas}
+タンク：{start_pressure} → {end_pressure}
+セットポイント：{setpoints}[ (PPO2：{min_ppo2} - {max_ppo2})]
+水面休息：{surface_interval}[ (残留窒素：{residual_n2})]
+ダイブコンピューター：{device}[ ({serial_number})]";
+
+/// The default template of the summary in the language
+pub fn default_template(lang: Lang) -> &'static str {
+    match lang {
+        Lang::En => DEFAULT_TEMPLATE,
+        Lang::Zh => ZH_TEMPLATE,
+        Lang::Ja => JA_TEMPLATE,
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct Summary {
     // It is a roughly position,
     // based on `start_position_lat`, `start_position_long`, and/or `end_position_lat`, `end_position_long`
-    // fit use `Sint32` for positions
-    pub location: (Option<i32>, Option<i32>),
+    // The sum of the positions as unit vectors, weighted by the time of each dive,
+    // so the average is on the sphere and also works across the antimeridian
+    pub position: Option<[f64; 3]>,
+    // The name of the dive site at the position
+    pub site: Option<String>,
 
     // Value for temperatures
-    pub avg_temperature: Option<i8>,
+    pub avg_temperature: Option<f64>,
     pub min_temperature: Option<i8>,
+    pub thermocline: Option<Thermocline>,
 
     // 0 for C
     pub temperature_unit: u8,
@@ -19,8 +87,46 @@ pub struct Summary {
     // 0 for m
     pub depth_unit: u8,
 
+    // bpm
+    pub avg_heart_rate: Option<f64>,
+    pub max_heart_rate: Option<u8>,
+
     // use total_elapsed_time to update average
     pub time: f64,
+    // The time covered by the averages after merging dives without the values,
+    // 0 for the whole `time`
+    pub temperature_time: f64,
+    pub depth_time: f64,
+    pub heart_rate_time: f64,
+
+    pub start_time: Option<DateTime<Local>>,
+    pub dive_number: Option<u32>,
+    // seconds from the previous dive
+    pub surface_interval: Option<u32>,
+    // The order in the input dives of the same day, None if it is the only one
+    pub dive_of_day: Option<u32>,
+    // The nitrogen loading of the tissues at the start in percent
+    pub start_n2: Option<u32>,
+    // oxygen and helium percentages of the enabled gases
+    pub gases: Vec<(u8, u8)>,
+    // bar
+    pub min_ppo2: Option<f64>,
+    pub max_ppo2: Option<f64>,
+    // The setpoints of a rebreather and the seconds on them, 0 for the open circuit
+    pub setpoints: Vec<(f64, f64)>,
+    // bar, of the first tank
+    pub start_pressure: Option<f64>,
+    pub end_pressure: Option<f64>,
+
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<u32>,
+
+    // The developer data index, the name and the text of the developer fields of the session
+    pub developer_fields: Vec<(u8, String, String)>,
+
+    // The entry and exit of each FIT file, ordered by the start time
+    pub waypoints: Vec<Waypoint>,
 }
 
 impl Summary {
@@ -40,22 +146,42 @@ impl Summary {
     }
 
     pub fn is_empty(&self) -> bool {
-        !(self.location.0.is_some()
-            || self.location.1.is_some()
+        !(self.position.is_some()
             || self.avg_temperature.is_some()
             || self.min_temperature.is_some()
             || self.avg_depth.is_some()
-            || self.max_depth.is_some())
+            || self.max_depth.is_some()
+            || self.start_time.is_some()
+            || self.dive_number.is_some())
     }
+
+    /// The average position in degrees
     pub fn location(&self) -> Option<(f64, f64)> {
-        if let (Some(lat), Some(long)) = self.location {
-            Some((
-                (lat as i64 * 180) as f64 / 2147483648f64,
-                (long as i64 * 180) as f64 / 2147483648f64,
-            ))
-        } else {
-            None
+        let [x, y, z] = self.position?;
+        if x.hypot(y).hypot(z) < f64::EPSILON {
+            // positions on the opposite sides of the earth have no average
+            return None;
         }
+        Some((z.atan2(x.hypot(y)).to_degrees(), y.atan2(x).to_degrees()))
+    }
+
+    /// Set the position from the semicircles of the FIT file,
+    /// the start and end positions of a dive are averaged and weighted by the dive time,
+    /// so `time` should be set before
+    pub fn set_positions(&mut self, positions: &[(i32, i32)]) {
+        if positions.is_empty() {
+            return;
+        }
+        let mut sum = [0f64; 3];
+        for (lat, long) in positions {
+            let lat = semicircles_to_radians(*lat);
+            let long = semicircles_to_radians(*long);
+            sum[0] += lat.cos() * long.cos();
+            sum[1] += lat.cos() * long.sin();
+            sum[2] += lat.sin();
+        }
+        let weight = if self.time > 0.0 { self.time } else { 1.0 } / positions.len() as f64;
+        self.position = Some(sum.map(|v| v * weight));
     }
 
     pub fn set_unit(&mut self, u: &str) -> Result<(), crate::error::Fit2SrtError> {
@@ -90,47 +216,60 @@ impl Summary {
         }
 
         let Summary {
-            location,
+            position,
+            site,
             avg_temperature,
             min_temperature,
+            thermocline,
             temperature_unit,
             avg_depth,
             max_depth,
             depth_unit,
+            avg_heart_rate,
+            max_heart_rate,
             time,
+            temperature_time,
+            depth_time,
+            heart_rate_time,
+            start_time,
+            dive_number,
+            surface_interval,
+            dive_of_day,
+            start_n2,
+            mut gases,
+            min_ppo2,
+            max_ppo2,
+            mut setpoints,
+            start_pressure,
+            end_pressure,
+            manufacturer,
+            product,
+            serial_number,
+            mut developer_fields,
+            mut waypoints,
         } = self;
 
-        let new_location = match (location, other.location) {
-            ((Some(old_lat), Some(old_long)), (Some(other_lat), Some(other_long))) => (
-                Some((old_lat + other_lat) / 2),
-                Some((old_long + other_long) / 2),
-            ),
-            ((Some(old_lat), Some(old_long)), (None, None)) => (Some(old_lat), Some(old_long)),
-            ((None, None), (Some(other_lat), Some(other_long))) => {
-                (Some(other_lat), Some(other_long))
+        let new_position = match (position, other.position) {
+            (Some(p), Some(other_p)) => {
+                Some([p[0] + other_p[0], p[1] + other_p[1], p[2] + other_p[2]])
             }
-            _ => (None, None),
+            (p, other_p) => p.or(other_p),
         };
 
         let total_time = time + other.time;
-
-        let new_avg_temperature = match (avg_temperature, other.avg_temperature) {
-            (Some(old_avg_t), Some(other_avg_t)) => Some(
-                ((old_avg_t as f64 * time + other_avg_t as f64 * other.time) / total_time) as i8,
-            ),
-            (Some(old_avg_t), None) => Some(old_avg_t),
-            (None, Some(other_avg_t)) => Some(other_avg_t),
-            _ => None,
-        };
-
-        let new_avg_depth = match (avg_depth, other.avg_depth) {
-            (Some(old_avg_d), Some(other_avg_d)) => {
-                Some((old_avg_d * time + other_avg_d * other.time) / total_time)
-            }
-            (Some(old_avg_d), None) => Some(old_avg_d),
-            (None, Some(other_avg_d)) => Some(other_avg_d),
-            _ => None,
-        };
+        let (new_avg_temperature, new_temperature_time) = weighted_mean(
+            (avg_temperature, temperature_time, time),
+            (other.avg_temperature, other.temperature_time, other.time),
+        );
+        let (new_avg_depth, new_depth_time) = weighted_mean(
+            (avg_depth, depth_time, time),
+            (other.avg_depth, other.depth_time, other.time),
+        );
+        let (new_avg_heart_rate, new_heart_rate_time) = weighted_mean(
+            (avg_heart_rate, heart_rate_time, time),
+            (other.avg_heart_rate, other.heart_rate_time, other.time),
+        );
+        let new_max_heart_rate = max_heart_rate.max(other.max_heart_rate);
 
         let new_min_temperature = match (min_temperature, other.min_temperature) {
             (Some(old_min_t), Some(other_min_t)) => {
@@ -158,15 +297,483 @@ impl Summary {
             _ => None,
         };
 
+        for gas in other.gases.iter() {
+            if !gases.contains(gas) {
+                gases.push(*gas);
+            }
+        }
+
+        for field in other.developer_fields.iter() {
+            if !developer_fields
+                .iter()
+                .any(|(index, name, _)| (index, name) == (&field.0, &field.1))
+            {
+                developer_fields.push(field.clone());
+            }
+        }
+
+        for (setpoint, secs) in other.setpoints.iter() {
+            add_time(&mut setpoints, *setpoint, *secs);
+        }
+
+        waypoints.extend(other.waypoints.iter().cloned());
+        waypoints.sort_by_key(|w| w.start_time);
+
+        // the pressures from the start of the earlier dive to the end of the later one
+        let other_first = match (start_time, other.start_time) {
+            (Some(t), Some(other_t)) => other_t < t,
+            (t, other_t) => t.is_none() && other_t.is_some(),
+        };
+        let (new_start_pressure, new_end_pressure) = if other_first {
+            (
+                other.start_pressure.or(start_pressure),
+                end_pressure.or(other.end_pressure),
+            )
+        } else {
+            (
+                start_pressure.or(other.start_pressure),
+                other.end_pressure.or(end_pressure),
+            )
+        };
+
+        // the surface interval is the one before the earlier dive
+        let (new_start_time, new_dive_number, new_surface_interval, new_dive_of_day, new_start_n2) =
+            match (start_time, other.start_time) {
+                (Some(t), Some(other_t)) if other_t < t => (
+                    Some(other_t),
+                    other.dive_number.or(dive_number),
+                    other.surface_interval.or(surface_interval),
+                    other.dive_of_day.or(dive_of_day),
+                    other.start_n2.or(start_n2),
+                ),
+                (None, Some(other_t)) => (
+                    Some(other_t),
+                    other.dive_number.or(dive_number),
+                    other.surface_interval.or(surface_interval),
+                    other.dive_of_day.or(dive_of_day),
+                    other.start_n2.or(start_n2),
+                ),
+                _ => (
+                    start_time,
+                    dive_number.or(other.dive_number),
+                    surface_interval.or(other.surface_interval),
+                    dive_of_day.or(other.dive_of_day),
+                    start_n2.or(other.start_n2),
+                ),
+            };
+
         Ok(Self {
-            location: new_location,
+            position: new_position,
+            site: site.or_else(|| other.site.clone()),
             avg_temperature: new_avg_temperature,
             min_temperature: new_min_temperature,
+            // the sharper one is shown
+            thermocline: match (thermocline, other.thermocline) {
+                (Some(t), Some(other_t)) if other_t.drop > t.drop => Some(other_t),
+                (t, other_t) => t.or(other_t),
+            },
             avg_depth: new_avg_depth,
             max_depth: new_max_depth,
             temperature_unit,
             depth_unit,
+            avg_heart_rate: new_avg_heart_rate,
+            max_heart_rate: new_max_heart_rate,
             time: total_time,
+            temperature_time: new_temperature_time,
+            depth_time: new_depth_time,
+            heart_rate_time: new_heart_rate_time,
+            start_time: new_start_time,
+            dive_number: new_dive_number,
+            surface_interval: new_surface_interval,
+            dive_of_day: new_dive_of_day,
+            start_n2: new_start_n2,
+            gases,
+            min_ppo2: match (min_ppo2, other.min_ppo2) {
+                (Some(p), Some(other_p)) => Some(p.min(other_p)),
+                (p, other_p) => p.or(other_p),
+            },
+            max_ppo2: match (max_ppo2, other.max_ppo2) {
+                (Some(p), Some(other_p)) => Some(p.max(other_p)),
+                (p, other_p) => p.or(other_p),
+            },
+            setpoints,
+            start_pressure: new_start_pressure,
+            end_pressure: new_end_pressure,
+            manufacturer: manufacturer.or_else(|| other.manufacturer.clone()),
+            product: product.or_else(|| other.product.clone()),
+            serial_number: serial_number.or(other.serial_number),
+            developer_fields,
+            waypoints,
         })
     }
+
+    /// The value of a field in the summary template
+    pub fn field(&self, name: &str) -> Option<String> {
+        self.field_in(name, &Locale::default())
+    }
+
+    /// The value of a field with the labels, the numbers and the date of the locale
+    pub fn field_in(&self, name: &str, locale: &Locale) -> Option<String> {
+        let value = match name {
+            "date" => self
+                .start_time
+                .map(|t| t.format(&locale.date_format).to_string()),
+            "time" => self.start_time.map(|t| t.format("%H:%M").to_string()),
+            "dive_time" => (self.time > 0.0).then(|| duration_format(self.time as i64)),
+            "location" => self
+                .site
+                .clone()
+                .or_else(|| self.field_in("coordinates", locale)),
+            "coordinates" => self
+                .location()
+                .map(|(lat, long)| format!("{lat:.6}, {long:.6}")),
+            "avg_temperature" => self
+                .avg_temperature
+                .map(|t| format!("{}{}", (t * 10.0).round() / 10.0, self.temp_unit())),
+            // the minimum is only shown if it is different from the average
+            "min_temperature" => self
+                .min_temperature
+                .filter(|t| Some(*t as f64) != self.avg_temperature.map(|t| t.round()))
+                .map(|t| format!("{t:}{}", self.temp_unit())),
+            "thermocline" => self.thermocline.map(|t| t.to_string()),
+            "avg_depth" => self.avg_depth.map(|d| format!("{d:}{}", self.depth_unit())),
+            "max_depth" => self.max_depth.map(|d| format!("{d:}{}", self.depth_unit())),
+            "avg_heart_rate" => self.avg_heart_rate.map(|hr| format!("{hr:.0}bpm")),
+            "max_heart_rate" => self.max_heart_rate.map(|hr| format!("{hr:}bpm")),
+            "gas" => (!self.gases.is_empty()).then(|| {
+                self.gases
+                    .iter()
+                    .map(|(o2, he)| locale.text(&gas_name(*o2, *he)).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }),
+            "setpoints" => {
+                (!self.setpoints.is_empty()).then(|| setpoints_format(&self.setpoints, locale))
+            }
+            "min_ppo2" => self.min_ppo2.map(|p| format!("{p:.2}")),
+            "max_ppo2" => self.max_ppo2.map(|p| format!("{p:.2}")),
+            "start_pressure" => self.start_pressure.map(|p| format!("{p:.0}bar")),
+            "end_pressure" => self.end_pressure.map(|p| format!("{p:.0}bar")),
+            "surface_interval" => self.surface_interval.map(|s| duration_format(s as i64)),
+            "dive_number" => self.dive_number.map(|n| n.to_string()),
+            "dive_of_day" => self.dive_of_day.map(|n| n.to_string()),
+            "residual_n2" => self.start_n2.map(|n| format!("{n}%")),
+            "device" => match (&self.manufacturer, &self.product) {
+                (Some(manufacturer), Some(product)) => Some(format!("{manufacturer} {product}")),
+                (Some(device), None) | (None, Some(device)) => Some(device.clone()),
+                (None, None) => None,
+            },
+            "serial_number" => self.serial_number.map(|n| n.to_string()),
+            // `dev:NAME` or `dev:INDEX:NAME`
+            name => {
+                let metric = name.parse::<Metric>().ok()?;
+                self.developer_fields
+                    .iter()
+                    .find(|(index, name, _)| metric.is_developer_field(*index, name))
+                    .map(|(_, _, text)| text.clone())
+            }
+        };
+        match name {
+            "avg_temperature" | "min_temperature" | "thermocline" | "avg_depth" | "max_depth"
+            | "min_ppo2" | "max_ppo2" => value.map(|v| locale.number(&v)),
+            _ => value,
+        }
+    }
+
+    /// Render the summary with a template, `{field}` is replaced by the value of the field.
+    /// A line is skipped if a field in it has no value,
+    /// except the fields in the optional part `[...]`, which skips only the part.
+    pub fn render(&self, template: &str) -> String {
+        self.render_in(template, &Locale::default())
+    }
+
+    /// Render the summary with the values in the locale
+    pub fn render_in(&self, template: &str, locale: &Locale) -> String {
+        let mut lines = Vec::new();
+        'line: for line in template.lines() {
+            let mut rendered = String::new();
+            let mut rest = line;
+            while let Some(open) = rest.find('[') {
+                let Some(close) = rest[open..].find(']').map(|i| i + open) else {
+                    break;
+                };
+                if let Some(part) = self.render_part(&rest[..open], locale) {
+                    rendered += &part;
+                } else {
+                    continue 'line;
+                }
+                rendered += &self
+                    .render_part(&rest[open + 1..close], locale)
+                    .unwrap_or_default();
+                rest = &rest[close + 1..];
+            }
+            if let Some(part) = self.render_part(rest, locale) {
+                rendered += &part;
+                lines.push(rendered);
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn render_part(&self, part: &str, locale: &Locale) -> Option<String> {
+        let mut rendered = String::new();
+        let mut rest = part;
+        while let Some(open) = rest.find('{') {
+            let Some(close) = rest[open..].find('}').map(|i| i + open) else {
+                break;
+            };
+            rendered += &rest[..open];
+            rendered += &self.field_in(&rest[open + 1..close], locale)?;
+            rest = &rest[close + 1..];
+        }
+        rendered += rest;
+        Some(rendered)
+    }
+}
+
+/// Number the dives of each day by the start time, and fill the surface intervals not in the FIT files
+/// from the end of the previous dive, the summaries should be ordered by the start time
+pub fn number_dives<'a>(summaries: impl IntoIterator<Item = &'a mut Summary>) {
+    let mut summaries: Vec<&mut Summary> = summaries.into_iter().collect();
+    let days: Vec<Option<NaiveDate>> = summaries
+        .iter()
+        .map(|s| s.start_time.map(|t| t.date_naive()))
+        .collect();
+    let mut previous: Option<(NaiveDate, u32, DateTime<Local>)> = None;
+    for summary in summaries.iter_mut() {
+        let Some(start) = summary.start_time else {
+            continue;
+        };
+        let day = start.date_naive();
+        let number = match previous {
+            Some((previous_day, n, _)) if previous_day == day => n + 1,
+            _ => 1,
+        };
+        if let (None, Some((_, _, end))) = (summary.surface_interval, previous) {
+            summary.surface_interval = u32::try_from((start - end).num_seconds()).ok();
+        }
+        if days.iter().filter(|d| **d == Some(day)).count() > 1 {
+            summary.dive_of_day = Some(number);
+        }
+        let end = start + TimeDelta::milliseconds((summary.time * 1000.0) as i64);
+        previous = Some((day, number, end));
+    }
+}
+
+/// The mean weighted by the time covered by the values, or the plain mean if there is no time,
+/// the inputs are the value, the time covered by the value and the dive time
+fn weighted_mean(a: (Option<f64>, f64, f64), b: (Option<f64>, f64, f64)) -> (Option<f64>, f64) {
+    let weight = |(value, value_time, time): (Option<f64>, f64, f64)| match value {
+        Some(_) if value_time > 0.0 => value_time,
+        Some(_) => time,
+        None => 0.0,
+    };
+    let (a_time, b_time) = (weight(a), weight(b));
+    let mean = match (a.0, b.0) {
+        (Some(a), Some(b)) if a_time + b_time > 0.0 => {
+            Some((a * a_time + b * b_time) / (a_time + b_time))
+        }
+        (Some(a), Some(b)) => Some((a + b) / 2.0),
+        (a, b) => a.or(b),
+    };
+    (mean, a_time + b_time)
+}
+
+fn semicircles_to_radians(semicircles: i32) -> f64 {
+    semicircles as f64 * std::f64::consts::PI / 2147483648f64
+}
+
+/// The latitude and longitude in degrees
+pub(crate) fn semicircles_to_degrees((lat, long): (i32, i32)) -> (f64, f64) {
+    (
+        semicircles_to_radians(lat).to_degrees(),
+        semicircles_to_radians(long).to_degrees(),
+    )
+}
+
+/// `1:02:03` or `2:03`
+pub(crate) fn duration_format(secs: i64) -> String {
+    if secs >= 3600 {
+        format!("{}:{:0>2}:{:0>2}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:0>2}", secs / 60, secs % 60)
+    }
+}
+
+/// `Air`, `EAN32`, `TX18/45`
+pub(crate) fn gas_name(o2: u8, he: u8) -> String {
+    match (o2, he) {
+        (21, 0) => "Air".to_string(),
+        (o2, 0) => format!("EAN{o2}"),
+        (o2, he) => format!("TX{o2}/{he}"),
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SummaryPosition {
+    Start,
+    #[default]
+    End,
+    Both,
+}
+
+impl FromStr for SummaryPosition {
+    type Err = Fit2SrtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "start" => Ok(Self::Start),
+            "end" => Ok(Self::End),
+            "both" => Ok(Self::Both),
+            _ => Err(Fit2SrtError::SummaryError(format!(
+                "unsupported position: {s}"
+            ))),
+        }
+    }
+}
+
+/// The cue of the dive summary,
+/// which shows after the delay from the start of the first cue and/or the end of the last cue
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SummaryCue {
+    // The default template of the language if it is none
+    pub template: Option<String>,
+    pub delay: TimeDelta,
+    pub duration: TimeDelta,
+    pub position: SummaryPosition,
+    // Show the summary of each FIT file instead of the one of all files
+    pub per_file: bool,
+}
+
+impl Default for SummaryCue {
+    fn default() -> Self {
+        Self {
+            template: None,
+            delay: TimeDelta::seconds(5),
+            duration: TimeDelta::seconds(10),
+            position: SummaryPosition::default(),
+            per_file: false,
+        }
+    }
+}
+
+#[test]
+fn render_summary() {
+    let summary = Summary {
+        avg_depth: Some(4.2),
+        max_depth: Some(8.9),
+        avg_temperature: Some(31.0),
+        gases: vec![(21, 0), (32, 0)],
+        time: 1878.885,
+        ..Default::default()
+    };
+    assert_eq!(
+        summary.render(DEFAULT_TEMPLATE),
+        "Summary:\nDive time: 31:18\nDepth: 4.2m (max: 8.9m)\nTemperature: 31C\nGas: Air, EAN32"
+    );
+}
+
+#[test]
+fn number_dives_of_day() {
+    use chrono::TimeZone;
+    let dive = |h, m| Summary {
+        start_time: Some(Local.with_ymd_and_hms(2024, 9, 7, h, m, 0).unwrap()),
+        time: 1800.0,
+        ..Default::default()
+    };
+    let mut summaries = vec![dive(9, 0), dive(11, 0), dive(14, 30)];
+    summaries[1].surface_interval = Some(5400);
+    summaries.push(Summary {
+        start_time: Some(Local.with_ymd_and_hms(2024, 9, 8, 9, 0, 0).unwrap()),
+        ..Default::default()
+    });
+    number_dives(summaries.iter_mut());
+    let numbers: Vec<_> = summaries.iter().map(|s| s.dive_of_day).collect();
+    assert_eq!(numbers, vec![Some(1), Some(2), Some(3), None]);
+    // the one of the dive computer is kept
+    assert_eq!(summaries[1].surface_interval, Some(5400));
+    assert_eq!(summaries[2].surface_interval, Some(10800));
+    assert_eq!(summaries[0].surface_interval, None);
+    summaries[2].start_n2 = Some(11);
+    assert_eq!(
+        summaries[2].render("Dive of the day: {dive_of_day}\nSurface interval: {surface_interval}[ (residual N2: {residual_n2})]"),
+        "Dive of the day: 3\nSurface interval: 3:00:00 (residual N2: 11%)"
+    );
+}
+
+#[test]
+fn average_across_antimeridian() {
+    let mut east = Summary {
+        time: 1.0,
+        ..Default::default()
+    };
+    // 10N 179E and 10N 179W
+    east.set_positions(&[(119304647, 2135672747)]);
+    let mut west = Summary {
+        time: 3.0,
+        ..Default::default()
+    };
+    west.set_positions(&[(119304647, -2135672747)]);
+    let (lat, long) = east.merge(&west).unwrap().location().unwrap();
+    assert!((lat - 10.0).abs() < 0.01);
+    assert!((long + 179.5).abs() < 0.01);
+}
+
+#[cfg(test)]
+fn summary_strategy() -> impl proptest::strategy::Strategy<Value = Summary> {
+    use proptest::prelude::*;
+    (
+        proptest::option::of((any::<i32>(), -1073741824..1073741824i32)),
+        proptest::option::of(-20.0..40.0f64),
+        proptest::option::of(-20..40i8),
+        proptest::option::of(0.0..100.0f64),
+        proptest::option::of(40..200u8),
+        1.0..10000.0f64,
+    )
+        .prop_map(
+            |(position, avg_temperature, min_temperature, avg_depth, heart_rate, time)| {
+                let mut summary = Summary {
+                    avg_temperature,
+                    min_temperature,
+                    avg_depth,
+                    max_depth: avg_depth.map(|d| d * 1.5),
+                    avg_heart_rate: heart_rate.map(|hr| hr as f64 * 0.8),
+                    max_heart_rate: heart_rate,
+                    time,
+                    ..Default::default()
+                };
+                if let Some((long, lat)) = position {
+                    summary.set_positions(&[(lat, long)]);
+                }
+                summary
+            },
+        )
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn merge_associative(a in summary_strategy(), b in summary_strategy(), c in summary_strategy()) {
+        let left = a.clone().merge(&b).unwrap().merge(&c).unwrap();
+        let right = a.merge(&b.merge(&c).unwrap()).unwrap();
+        let close = |x: Option<f64>, y: Option<f64>| match (x, y) {
+            (Some(x), Some(y)) => (x - y).abs() < 1e-6 * x.abs().max(1.0),
+            (x, y) => x.is_none() && y.is_none(),
+        };
+        proptest::prop_assert!(close(left.avg_temperature, right.avg_temperature));
+        proptest::prop_assert!(close(left.avg_depth, right.avg_depth));
+        proptest::prop_assert_eq!(left.max_depth, right.max_depth);
+        proptest::prop_assert_eq!(left.min_temperature, right.min_temperature);
+        proptest::prop_assert!(close(left.avg_heart_rate, right.avg_heart_rate));
+        proptest::prop_assert_eq!(left.max_heart_rate, right.max_heart_rate);
+        proptest::prop_assert!(close(Some(left.time), Some(right.time)));
+        match (left.location(), right.location()) {
+            (Some((lat, long)), Some((other_lat, other_long))) => {
+                proptest::prop_assert!((lat - other_lat).abs() < 1e-6);
+                let diff = (long - other_long).abs();
+                proptest::prop_assert!(diff < 1e-6 || (diff - 360.0).abs() < 1e-6);
+            }
+            (l, r) => proptest::prop_assert_eq!(l, r),
+        }
+    }
 }
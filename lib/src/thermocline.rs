@@ -0,0 +1,70 @@
+/// A layer of water where the temperature drops sharply with the depth
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Thermocline {
+    // The top of the layer
+    pub depth: f64,
+    // The temperature drops across the layer
+    pub drop: f64,
+}
+
+impl std::fmt::Display for Thermocline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1}m (-{:.1}C)", self.depth, self.drop)
+    }
+}
+
+impl Thermocline {
+    /// Find the steepest drop of the temperature over the depth
+    /// from the samples of depth and temperature,
+    /// the temperatures are averaged for every metre to smooth the lag of the sensor,
+    /// `min_gradient` is the least drop per metre to be a thermocline
+    pub fn detect(profile: &[(f64, f64)], min_gradient: f64) -> Option<Self> {
+        let mut bins: Vec<(f64, usize)> = Vec::new();
+        for (depth, temperature) in profile {
+            if !depth.is_finite() || *depth < 0.0 || !temperature.is_finite() {
+                continue;
+            }
+            let bin = depth.round() as usize;
+            if bins.len() <= bin {
+                bins.resize(bin + 1, (0.0, 0));
+            }
+            bins[bin].0 += temperature;
+            bins[bin].1 += 1;
+        }
+        let layers: Vec<(f64, f64)> = bins
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (_, n))| *n > 0)
+            .map(|(depth, (sum, n))| (depth as f64, sum / n as f64))
+            .collect();
+        layers
+            .windows(2)
+            .map(|w| {
+                let ((upper_depth, upper_t), (lower_depth, lower_t)) = (w[0], w[1]);
+                let drop = upper_t - lower_t;
+                (drop / (lower_depth - upper_depth), upper_depth, drop)
+            })
+            .filter(|(gradient, _, _)| *gradient >= min_gradient)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, depth, drop)| Self { depth, drop })
+    }
+}
+
+#[test]
+fn detect_thermocline() {
+    let profile: Vec<(f64, f64)> = (0..30)
+        .map(|d| {
+            let depth = d as f64;
+            (depth, if depth < 12.0 { 28.0 } else { 24.0 })
+        })
+        .collect();
+    assert_eq!(
+        Thermocline::detect(&profile, 0.5),
+        Some(Thermocline {
+            depth: 11.0,
+            drop: 4.0
+        })
+    );
+    let profile: Vec<(f64, f64)> = (0..30).map(|d| (d as f64, 28.0 - d as f64 * 0.1)).collect();
+    assert_eq!(Thermocline::detect(&profile, 0.5), None);
+}
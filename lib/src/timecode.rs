@@ -0,0 +1,173 @@
+use chrono::TimeDelta;
+use std::str::FromStr;
+
+use crate::error::Fit2SrtError;
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameRate {
+    // frames in `den` seconds, for example 30000 / 1001 for 29.97
+    num: u32,
+    den: u32,
+    drop_frame: bool,
+}
+
+impl FrameRate {
+    pub fn new(num: u32, den: u32, drop_frame: bool) -> Result<Self, Fit2SrtError> {
+        if num == 0 || den == 0 {
+            return Err(Fit2SrtError::FrameRateError(format!("{num}/{den}")));
+        }
+        // drop frame timecode is only defined for 29.97 and 59.94
+        if drop_frame && !(den == 1001 && (num == 30000 || num == 60000)) {
+            return Err(Fit2SrtError::FrameRateError(format!(
+                "{num}/{den} can not be drop frame"
+            )));
+        }
+        Ok(Self {
+            num,
+            den,
+            drop_frame,
+        })
+    }
+
+    pub fn fps(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    pub fn is_drop_frame(&self) -> bool {
+        self.drop_frame
+    }
+
+    /// The frames counted in one second of timecode, 30 for 29.97
+    fn nominal_fps(&self) -> i64 {
+        self.num.div_ceil(self.den) as i64
+    }
+
+    /// The index of the frame nearest to the time
+    pub fn frame(&self, t: &TimeDelta) -> i64 {
+        let ns = t.num_nanoseconds().unwrap_or(i64::MAX) as i128;
+        let frame_ns = self.den as i128 * NANOS_PER_SEC;
+        ((ns * self.num as i128 + frame_ns / 2).div_euclid(frame_ns)) as i64
+    }
+
+    /// The start time of the frame, rounded up to milliseconds so it still lands on the frame
+    pub fn frame_time(&self, frame: i64) -> TimeDelta {
+        let ms = (frame as i128 * self.den as i128 * 1000 + self.num as i128 - 1)
+            .div_euclid(self.num as i128);
+        TimeDelta::milliseconds(ms as i64)
+    }
+
+    pub fn snap(&self, t: &TimeDelta) -> TimeDelta {
+        self.frame_time(self.frame(t))
+    }
+
+    /// SMPTE timecode `HH:MM:SS:FF`, or `HH:MM:SS;FF` for drop frame
+    pub fn timecode(&self, t: &TimeDelta) -> String {
+        let fps = self.nominal_fps();
+        let mut frame = self.frame(t);
+        if self.drop_frame {
+            // skip the frame labels 0 and 1 (0 to 3 for 59.94) every minute,
+            // except every tenth minute
+            let drop = fps / 15;
+            let frames_per_minute = fps * 60 - drop;
+            let frames_per_ten_minutes = fps * 600 - drop * 9;
+            let tens = frame / frames_per_ten_minutes;
+            let rest = frame % frames_per_ten_minutes;
+            frame += drop * 9 * tens;
+            if rest > drop {
+                frame += drop * ((rest - drop) / frames_per_minute);
+            }
+        }
+        format!(
+            "{:0>2}:{:0>2}:{:0>2}{}{:0>2}",
+            frame / (fps * 3600),
+            frame / (fps * 60) % 60,
+            frame / fps % 60,
+            if self.drop_frame { ';' } else { ':' },
+            frame % fps
+        )
+    }
+}
+
+impl FromStr for FrameRate {
+    type Err = Fit2SrtError;
+
+    /// Parse `25`, `29.97`, `29.97df`, `59.94df`, ...
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let (rate, drop_frame) = if let Some(rate) = s.strip_suffix("df") {
+            (rate.trim_end_matches([' ', '-']), true)
+        } else {
+            (s.as_str(), false)
+        };
+        match rate {
+            "23.976" | "23.98" => Self::new(24000, 1001, drop_frame),
+            "29.97" => Self::new(30000, 1001, drop_frame),
+            "47.952" | "47.95" => Self::new(48000, 1001, drop_frame),
+            "59.94" => Self::new(60000, 1001, drop_frame),
+            _ => match rate.parse::<u32>() {
+                Ok(fps) => Self::new(fps, 1, drop_frame),
+                Err(_) => Err(Fit2SrtError::FrameRateError(s.to_string())),
+            },
+        }
+    }
+}
+
+/// How the times of cues are written
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// `00:00:00,000`
+    #[default]
+    Srt,
+    /// `00:00:00,000` on the frame boundaries
+    SrtFrames(FrameRate),
+    /// SMPTE timecode `00:00:00:00`
+    Smpte(FrameRate),
+}
+
+impl TimeFormat {
+    pub fn snap(&self, t: &TimeDelta) -> TimeDelta {
+        match self {
+            Self::Srt => *t,
+            Self::SrtFrames(rate) | Self::Smpte(rate) => rate.snap(t),
+        }
+    }
+
+    pub fn format(&self, t: &TimeDelta) -> String {
+        match self {
+            Self::Srt => crate::srt_iter::delta_srt_format(t),
+            Self::SrtFrames(rate) => crate::srt_iter::delta_srt_format(&rate.snap(t)),
+            Self::Smpte(rate) => rate.timecode(t),
+        }
+    }
+}
+
+#[test]
+fn snap_to_frames() {
+    let rate: FrameRate = "25".parse().unwrap();
+    assert_eq!(
+        rate.snap(&TimeDelta::milliseconds(1030)),
+        TimeDelta::milliseconds(1040)
+    );
+    let rate: FrameRate = "29.97".parse().unwrap();
+    assert_eq!(
+        rate.snap(&TimeDelta::seconds(1)),
+        TimeDelta::milliseconds(1001)
+    );
+    assert_eq!(rate.frame(&rate.snap(&TimeDelta::seconds(1))), 30);
+}
+
+#[test]
+fn smpte_timecode() {
+    let rate: FrameRate = "25".parse().unwrap();
+    assert_eq!(
+        rate.timecode(&TimeDelta::milliseconds(3_723_520)),
+        "01:02:03:13"
+    );
+    let rate: FrameRate = "29.97df".parse().unwrap();
+    assert_eq!(rate.timecode(&rate.frame_time(1799)), "00:00:59;29");
+    assert_eq!(rate.timecode(&rate.frame_time(1800)), "00:01:00;02");
+    assert_eq!(rate.timecode(&rate.frame_time(17982)), "00:10:00;00");
+    assert!("25df".parse::<FrameRate>().is_err());
+}
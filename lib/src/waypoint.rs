@@ -0,0 +1,151 @@
+use chrono::{DateTime, Local, SecondsFormat, TimeDelta, Utc};
+
+use crate::summary::duration_format;
+
+// The position, name and time of a point
+type NamedPoint = ((f64, f64), String, Option<DateTime<Local>>);
+
+/// The surface positions of a dive from a FIT file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Waypoint {
+    // latitude and longitude in degrees
+    pub entry: Option<(f64, f64)>,
+    pub exit: Option<(f64, f64)>,
+    pub start_time: Option<DateTime<Local>>,
+    // seconds
+    pub duration: f64,
+    pub dive_number: Option<u32>,
+    pub max_depth: Option<f64>,
+    pub depth_unit: &'static str,
+}
+
+impl Waypoint {
+    fn name(&self, point: &str) -> String {
+        if let Some(n) = self.dive_number {
+            format!("Dive #{n} {point}")
+        } else {
+            format!("Dive {point}")
+        }
+    }
+
+    fn description(&self) -> String {
+        let mut description = Vec::new();
+        if let Some(t) = self.start_time {
+            description.push(format!("Date: {}", t.format("%Y-%m-%d %H:%M")));
+        }
+        if let Some(d) = self.max_depth {
+            description.push(format!("Max depth: {d:}{}", self.depth_unit));
+        }
+        if self.duration > 0.0 {
+            description.push(format!(
+                "Duration: {}",
+                duration_format(self.duration as i64)
+            ));
+        }
+        description.join(", ")
+    }
+
+    /// The entry and the exit with their names and times
+    fn points(&self) -> Vec<NamedPoint> {
+        let mut points = Vec::new();
+        if let Some(entry) = self.entry {
+            points.push((entry, self.name("entry"), self.start_time));
+        }
+        if let Some(exit) = self.exit {
+            let end_time = self
+                .start_time
+                .map(|t| t + TimeDelta::milliseconds((self.duration * 1000.0) as i64));
+            points.push((exit, self.name("exit"), end_time));
+        }
+        points
+    }
+}
+
+/// GPX 1.1 with a waypoint for each entry and exit
+pub fn gpx(waypoints: &[Waypoint]) -> String {
+    let mut gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="fit2srt" xmlns="http://www.topografix.com/GPX/1/1">
+"#
+    .to_string();
+    for waypoint in waypoints {
+        for ((lat, long), name, time) in waypoint.points() {
+            gpx += &format!("  <wpt lat=\"{lat:.7}\" lon=\"{long:.7}\">\n");
+            if let Some(time) = time {
+                gpx += &format!("    <time>{}</time>\n", utc_time(&time));
+            }
+            gpx += &format!("    <name>{}</name>\n", escape(&name));
+            gpx += &format!("    <desc>{}</desc>\n", escape(&waypoint.description()));
+            gpx += "  </wpt>\n";
+        }
+    }
+    gpx += "</gpx>\n";
+    gpx
+}
+
+/// KML 2.2 with a placemark for each entry and exit
+pub fn kml(waypoints: &[Waypoint]) -> String {
+    let mut kml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+<Document>
+  <name>fit2srt</name>
+"#
+    .to_string();
+    for waypoint in waypoints {
+        for ((lat, long), name, time) in waypoint.points() {
+            kml += "  <Placemark>\n";
+            kml += &format!("    <name>{}</name>\n", escape(&name));
+            kml += &format!(
+                "    <description>{}</description>\n",
+                escape(&waypoint.description())
+            );
+            if let Some(time) = time {
+                kml += &format!(
+                    "    <TimeStamp><when>{}</when></TimeStamp>\n",
+                    utc_time(&time)
+                );
+            }
+            kml += &format!("    <Point><coordinates>{long:.7},{lat:.7}</coordinates></Point>\n");
+            kml += "  </Placemark>\n";
+        }
+    }
+    kml += "</Document>\n</kml>\n";
+    kml
+}
+
+fn utc_time(t: &DateTime<Local>) -> String {
+    t.with_timezone(&Utc)
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[test]
+fn export_waypoints() {
+    use chrono::TimeZone;
+    let waypoints = [Waypoint {
+        entry: Some((21.9392, 120.7453)),
+        exit: None,
+        start_time: Some(Local.timestamp_opt(1725678600, 0).unwrap()),
+        duration: 1878.885,
+        dive_number: Some(19),
+        max_depth: Some(8.908),
+        depth_unit: "m",
+    }];
+    let gpx = gpx(&waypoints);
+    assert!(gpx.starts_with(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="fit2srt" xmlns="http://www.topografix.com/GPX/1/1">
+  <wpt lat="21.9392000" lon="120.7453000">
+    <time>2024-09-07T03:10:00Z</time>
+    <name>Dive #19 entry</name>
+"#
+    ));
+    assert!(gpx.ends_with(", Max depth: 8.908m, Duration: 31:18</desc>\n  </wpt>\n</gpx>\n"));
+    assert!(kml(&waypoints)
+        .contains("<Point><coordinates>120.7453000,21.9392000</coordinates></Point>"));
+}